@@ -1,8 +1,11 @@
 use bevy::prelude::*;
-use crate::game::game_state::{GameState, GamePhase};
-use crate::game::worm::{Worm, PlayerControlled};
-use crate::game::aiming::AimingState;
-use crate::game::weapons::{WeaponInventory, fire_weapon};
+use crate::game::game_state::{GameState, GamePhase, TurnFlow};
+use crate::game::worm::{Worm, ActiveWorm};
+use crate::game::aiming::{AimingState, wrap_angle};
+use crate::game::events::{WormCommand, IssueWormCommands};
+use crate::game::weapons::{WeaponInventory, WeaponCategory};
+use crate::game::replay::{simulation_running, watching_replay};
+use crate::game::scheme::AIDifficulty;
 
 pub struct AIPlugin;
 
@@ -10,10 +13,11 @@ impl Plugin for AIPlugin {
     fn build(&self, app: &mut App) {
         app
             .insert_resource(AIController::default())
+            // A replay plays back the commands the AI sent instead
             .add_systems(Update, (
                 ai_decision_making,
-                ai_execute_action,
-            ));
+                ai_execute_action.in_set(IssueWormCommands),
+            ).run_if(simulation_running.and(not(watching_replay))));
     }
 }
 
@@ -24,16 +28,29 @@ pub struct AIController {
     pub target_angle: f32,
     pub target_power: f32,
     pub action_timer: Timer,
+    /// How far off the aim can be either way, in degrees
+    pub aim_error: f32,
+    /// Kept apart from `MatchRng` so a replay, which plays back the AI's commands
+    /// without running it, draws the same match numbers
+    pub rng: fastrand::Rng,
 }
 
 impl Default for AIController {
     fn default() -> Self {
+        Self::new(AIDifficulty::Normal, 0)
+    }
+}
+
+impl AIController {
+    pub fn new(difficulty: AIDifficulty, seed: u64) -> Self {
         Self {
-            thinking_time: Timer::from_seconds(2.0, TimerMode::Once),
+            thinking_time: Timer::from_seconds(difficulty.thinking_time(), TimerMode::Once),
             current_action: AIAction::Thinking,
             target_angle: 45.0,
             target_power: 0.5,
             action_timer: Timer::from_seconds(0.5, TimerMode::Once),
+            aim_error: difficulty.aim_error(),
+            rng: fastrand::Rng::with_seed(seed),
         }
     }
 }
@@ -41,7 +58,6 @@ impl Default for AIController {
 #[derive(Debug, PartialEq)]
 pub enum AIAction {
     Thinking,
-    Moving,
     Aiming,
     Firing,
     Done,
@@ -53,85 +69,93 @@ pub struct AIControlled;
 fn ai_decision_making(
     mut ai_controller: ResMut<AIController>,
     game_state: Res<GameState>,
+    phase: Res<State<GamePhase>>,
     time: Res<Time>,
-    ai_worm_query: Query<(&Transform, &Worm), (With<AIControlled>, Without<PlayerControlled>)>,
-    target_worm_query: Query<(&Transform, &Worm), (With<PlayerControlled>, Without<AIControlled>)>,
+    ai_worm_query: Query<&Transform, (With<AIControlled>, With<ActiveWorm>)>,
+    target_worm_query: Query<(&Transform, &Worm)>,
 ) {
     // Only act when it's AI's turn and in player turn phase
-    if game_state.game_phase != GamePhase::PlayerTurn {
+    if *phase.get() != GamePhase::PlayerTurn {
         return;
     }
     
-    // Check if current player is AI controlled
-    let is_ai_turn = ai_worm_query.iter().any(|(_, worm)| 
-        worm.team == game_state.current_player && worm.health > 0.0
-    );
+    // The active worm is only AI-controlled on an AI team's turn
+    let Some(ai_transform) = ai_worm_query.iter().next() else {
+        return;
+    };
     
-    if !is_ai_turn {
+    // Other actions are handled in ai_execute_action
+    if ai_controller.current_action != AIAction::Thinking {
         return;
     }
-    
-    match ai_controller.current_action {
-        AIAction::Thinking => {
-            ai_controller.thinking_time.tick(time.delta());
-            if ai_controller.thinking_time.finished() {
-                // Make AI decision
-                if let Some((ai_transform, _)) = ai_worm_query.iter()
-                    .find(|(_, worm)| worm.team == game_state.current_player) {
-                    
-                    if let Some((target_transform, _)) = target_worm_query.iter().next() {
-                        // Calculate angle and power to hit target
-                        let distance = target_transform.translation - ai_transform.translation;
-                        
-                        // Simple AI: aim roughly at target with some randomness
-                        ai_controller.target_angle = distance.y.atan2(distance.x).to_degrees() 
-                            + (fastrand::f32() - 0.5) * 30.0; // Add some inaccuracy
-                        
-                        let distance_factor = distance.length() / 500.0;
-                        ai_controller.target_power = (distance_factor * 0.8 + 0.2)
-                            .clamp(0.3, 1.0);
-                        
-                        ai_controller.current_action = AIAction::Aiming;
-                        ai_controller.action_timer.reset();
-                    }
-                }
-            }
+
+    ai_controller.thinking_time.tick(time.delta());
+    if ai_controller.thinking_time.finished() {
+        // Make AI decision: go for the nearest enemy worm
+        let target = target_worm_query.iter()
+            .filter(|(_, worm)| !game_state.are_allies(worm.team, game_state.current_player) && worm.health > 0.0)
+            .min_by(|(a, _), (b, _)| {
+                a.translation.distance(ai_transform.translation)
+                    .total_cmp(&b.translation.distance(ai_transform.translation))
+            });
+        
+        if let Some((target_transform, _)) = target {
+            // Calculate angle and power to hit target
+            let distance = target_transform.translation - ai_transform.translation;
+            
+            // Simple AI: aim roughly at target with some randomness
+            let error = (ai_controller.rng.f32() - 0.5) * 2.0 * ai_controller.aim_error;
+            ai_controller.target_angle = distance.y.atan2(distance.x).to_degrees() + error;
+            
+            let distance_factor = distance.length() / 500.0;
+            ai_controller.target_power = (distance_factor * 0.8 + 0.2)
+                .clamp(0.3, 1.0);
+            
+            ai_controller.current_action = AIAction::Aiming;
+            ai_controller.action_timer.reset();
         }
-        _ => {} // Other actions handled in ai_execute_action
     }
 }
 
+/// Plays the decision out through the same `WormCommand`s a player's keys send.
 fn ai_execute_action(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
     mut ai_controller: ResMut<AIController>,
-    mut aiming_state: ResMut<AimingState>,
-    mut game_state: ResMut<GameState>,
+    aiming_state: Res<AimingState>,
+    turn: TurnFlow,
     weapon_inventory: Res<WeaponInventory>,
     time: Res<Time>,
-    ai_worm_query: Query<&Transform, (With<AIControlled>, With<Worm>)>,
+    ai_worm_query: Query<&Transform, (With<AIControlled>, With<ActiveWorm>)>,
+    mut worm_commands: EventWriter<WormCommand>,
 ) {
     // Only execute when it's AI's turn
-    let is_ai_turn = ai_worm_query.iter().any(|_| true); // Simplified check
-    if !is_ai_turn || game_state.current_player != 1 { // Assume AI is player 1
+    if ai_worm_query.is_empty() {
         return;
     }
     
     match ai_controller.current_action {
         AIAction::Aiming => {
+            // The AI only knows how to aim, so it switches to a weapon it can fire first
+            let holding_projectile = weapon_inventory.weapons.get(weapon_inventory.current_weapon)
+                .is_some_and(|weapon| weapon.category() == WeaponCategory::Projectile);
+            if !holding_projectile {
+                if let Some(index) = weapon_inventory.weapons.iter()
+                    .position(|weapon| weapon.category() == WeaponCategory::Projectile) {
+                    worm_commands.write(WormCommand::SelectWeapon(index));
+                }
+                return;
+            }
+            
             // Start aiming
             if !aiming_state.is_aiming {
-                aiming_state.is_aiming = true;
-                game_state.start_aiming();
+                worm_commands.write(WormCommand::ToggleAim);
             }
             
-            // Gradually adjust aim to target
-            let angle_diff = ai_controller.target_angle - aiming_state.aim_angle;
+            // Gradually adjust aim to target, turning the short way round
+            let angle_diff = wrap_angle(ai_controller.target_angle - aiming_state.aim_angle);
             if angle_diff.abs() > 2.0 {
-                aiming_state.aim_angle += angle_diff.signum() * 30.0 * time.delta_secs();
+                worm_commands.write(WormCommand::AimBy(angle_diff.signum() * 30.0 * time.delta_secs()));
             } else {
-                aiming_state.aim_angle = ai_controller.target_angle;
+                worm_commands.write(WormCommand::AimBy(angle_diff));
                 ai_controller.current_action = AIAction::Firing;
                 ai_controller.action_timer.reset();
             }
@@ -142,59 +166,21 @@ fn ai_execute_action(
             
             // Start charging power
             if !aiming_state.power_charging {
-                aiming_state.power_charging = true;
-                aiming_state.power = 0.0;
-            }
-            
-            // Charge to target power
-            if aiming_state.power < ai_controller.target_power {
-                aiming_state.power += time.delta_secs() * 0.8;
-            } else {
+                worm_commands.write(WormCommand::BeginCharge);
+            } else if aiming_state.power >= ai_controller.target_power {
                 // Fire!
-                if let Some(current_weapon) = weapon_inventory.weapons.get(weapon_inventory.current_weapon) {
-                    if let Some(ai_transform) = ai_worm_query.iter().next() {
-                        let angle_rad = aiming_state.aim_angle.to_radians();
-                        let direction = Vec2::new(angle_rad.cos(), angle_rad.sin());
-                        
-                        let firing_position = ai_transform.translation + Vec3::new(
-                            direction.x * 20.0,
-                            direction.y * 20.0,
-                            0.1,
-                        );
-                        
-                        fire_weapon(
-                            &mut commands,
-                            &mut meshes,
-                            &mut materials,
-                            current_weapon.clone(),
-                            firing_position,
-                            direction,
-                            aiming_state.power,
-                        );
-                        
-                        // Update game state
-                        game_state.start_firing();
-                        game_state.projectile_launched();
-                        
-                        // Reset AI state
-                        aiming_state.is_aiming = false;
-                        aiming_state.power_charging = false;
-                        aiming_state.power = 0.5;
-                        
-                        ai_controller.current_action = AIAction::Done;
-                        ai_controller.thinking_time.reset();
-                    }
-                }
+                worm_commands.write(WormCommand::Release);
+                
+                ai_controller.current_action = AIAction::Done;
+                ai_controller.thinking_time.reset();
             }
         }
         
-        AIAction::Done => {
-            // Wait for turn to end naturally
-            if game_state.game_phase == GamePhase::PlayerTurn {
-                ai_controller.current_action = AIAction::Thinking;
-            }
+        // Wait for turn to end naturally
+        AIAction::Done if *turn.phase() == GamePhase::PlayerTurn => {
+            ai_controller.current_action = AIAction::Thinking;
         }
         
         _ => {}
     }
-}
\ No newline at end of file
+}
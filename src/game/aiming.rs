@@ -1,7 +1,9 @@
 use bevy::prelude::*;
-use crate::game::weapons::{WeaponInventory, WindSystem, fire_weapon};
-use crate::game::worm::{Worm, PlayerControlled};
-use crate::game::game_state::{GameState, GamePhase};
+use crate::game::weapons::{WeaponInventory, WeaponCategory, WindSystem, fire_weapon};
+use crate::game::worm::{Worm, PlayerControlled, ActiveWorm};
+use crate::game::events::{WormCommand, IssueWormCommands};
+use crate::game::replay::simulation_running;
+use crate::game::game_state::{GameState, GamePhase, TurnFlow};
 
 pub struct AimingPlugin;
 
@@ -10,25 +12,23 @@ impl Plugin for AimingPlugin {
         app
             .insert_resource(AimingState::default())
             .add_systems(Update, (
-                handle_aiming_input,
+                handle_aiming_input.after(IssueWormCommands),
                 update_trajectory_preview,
-                handle_weapon_switching,
-                handle_firing,
+                handle_weapon_switching.after(IssueWormCommands),
+                handle_firing.after(IssueWormCommands),
                 cleanup_trajectory_preview,
-            ));
+            ).run_if(simulation_running));
     }
 }
 
 #[derive(Resource)]
 pub struct AimingState {
     pub is_aiming: bool,
+    /// Degrees anticlockwise from pointing right, all the way round so worms can fire either way
     pub aim_angle: f32,
     pub power: f32,
     pub max_power: f32,
     pub power_charging: bool,
-    pub trajectory_points: Vec<Vec2>,
-    pub last_calculated_angle: f32,
-    pub last_calculated_power: f32,
 }
 
 impl Default for AimingState {
@@ -39,14 +39,15 @@ impl Default for AimingState {
             power: 0.5,
             max_power: 1.0,
             power_charging: false,
-            trajectory_points: Vec::new(),
-            last_calculated_angle: 0.0,
-            last_calculated_power: 0.0,
-        }
         }
     }
 }
 
+/// An angle in degrees brought into -180..180.
+pub fn wrap_angle(degrees: f32) -> f32 {
+    (degrees + 180.0).rem_euclid(360.0) - 180.0
+}
+
 fn cleanup_trajectory_preview(
     mut commands: Commands,
     aiming_state: Res<AimingState>,
@@ -66,7 +67,7 @@ fn cleanup_trajectory_preview(
             commands.entity(entity).despawn();
         }
     }
-}}
+}
 
 #[derive(Component)]
 pub struct TrajectoryPreview;
@@ -78,28 +79,52 @@ pub struct AimingCrosshair;
 pub struct PowerBar;
 
 fn handle_aiming_input(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut worm_commands: EventReader<WormCommand>,
     mut aiming_state: ResMut<AimingState>,
-    mut game_state: ResMut<GameState>,
+    mut turn: TurnFlow,
+    weapon_inventory: Res<WeaponInventory>,
     time: Res<Time>,
 ) {
     // Only allow aiming during player's turn
-    if !game_state.can_player_act() {
+    if !turn.can_player_act() {
+        worm_commands.clear();
         return;
     }
     
-    // Toggle aiming mode
-    if keyboard_input.just_pressed(KeyCode::Space) {
-        if !aiming_state.is_aiming {
-            aiming_state.is_aiming = true;
-            game_state.start_aiming();
-            println!("Entered aiming mode"); // Debug output
-        } else {
-            aiming_state.is_aiming = false;
-            aiming_state.power = 0.0;
-            aiming_state.power_charging = false;
-            game_state.start_new_turn(); // Return to turn mode
-            println!("Exited aiming mode"); // Debug output
+    // Other weapon categories have their own input flow
+    let uses_aiming = weapon_inventory.weapons.get(weapon_inventory.current_weapon)
+        .is_some_and(|weapon| weapon.category() == WeaponCategory::Projectile);
+    if !uses_aiming {
+        aiming_state.is_aiming = false;
+        aiming_state.power_charging = false;
+        worm_commands.clear();
+        return;
+    }
+    
+    for command in worm_commands.read() {
+        match *command {
+            // Toggle aiming mode
+            WormCommand::ToggleAim => {
+                if !aiming_state.is_aiming {
+                    aiming_state.is_aiming = true;
+                    turn.start_aiming();
+                    println!("Entered aiming mode"); // Debug output
+                } else {
+                    aiming_state.is_aiming = false;
+                    aiming_state.power = 0.0;
+                    aiming_state.power_charging = false;
+                    turn.cancel_aiming(); // Return to turn mode
+                    println!("Exited aiming mode"); // Debug output
+                }
+            }
+            WormCommand::AimBy(degrees) if aiming_state.is_aiming => {
+                aiming_state.aim_angle = wrap_angle(aiming_state.aim_angle + degrees);
+            }
+            WormCommand::BeginCharge if aiming_state.is_aiming => {
+                aiming_state.power_charging = true;
+                aiming_state.power = 0.0;
+            }
+            _ => {}
         }
     }
     
@@ -107,23 +132,7 @@ fn handle_aiming_input(
         return;
     }
     
-    // Adjust aim angle
-    if keyboard_input.pressed(KeyCode::ArrowLeft) {
-        aiming_state.aim_angle += 90.0 * time.delta_secs();
-    }
-    if keyboard_input.pressed(KeyCode::ArrowRight) {
-        aiming_state.aim_angle -= 90.0 * time.delta_secs();
-    }
-    
-    // Clamp angle to reasonable range
-    aiming_state.aim_angle = aiming_state.aim_angle.clamp(-90.0, 90.0);
-    
     // Power charging
-    if keyboard_input.just_pressed(KeyCode::Enter) {
-        aiming_state.power_charging = true;
-        aiming_state.power = 0.0;
-    }
-    
     if aiming_state.power_charging {
         aiming_state.power += time.delta_secs() * 0.8; // Charge speed
         if aiming_state.power >= aiming_state.max_power {
@@ -133,32 +142,21 @@ fn handle_aiming_input(
 }
 
 fn handle_weapon_switching(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut worm_commands: EventReader<WormCommand>,
     mut weapon_inventory: ResMut<WeaponInventory>,
-    game_state: Res<GameState>,
+    phase: Res<State<GamePhase>>,
 ) {
-    if game_state.game_phase != GamePhase::PlayerTurn {
+    if *phase.get() != GamePhase::PlayerTurn {
+        worm_commands.clear();
         return;
     }
     
-    if keyboard_input.just_pressed(KeyCode::Digit1) {
-        weapon_inventory.current_weapon = 0;
-    }
-    if keyboard_input.just_pressed(KeyCode::Digit2) {
-        weapon_inventory.current_weapon = 1;
-    }
-    if keyboard_input.just_pressed(KeyCode::Digit3) {
-        weapon_inventory.current_weapon = 2;
-    }
-    
-    // Cycle weapons with Q/E
-    if keyboard_input.just_pressed(KeyCode::KeyQ) {
-        weapon_inventory.current_weapon = 
-            (weapon_inventory.current_weapon + weapon_inventory.weapons.len() - 1) % weapon_inventory.weapons.len();
-    }
-    if keyboard_input.just_pressed(KeyCode::KeyE) {
-        weapon_inventory.current_weapon = 
-            (weapon_inventory.current_weapon + 1) % weapon_inventory.weapons.len();
+    for command in worm_commands.read() {
+        if let WormCommand::SelectWeapon(index) = *command {
+            if index < weapon_inventory.weapons.len() {
+                weapon_inventory.current_weapon = index;
+            }
+        }
     }
 }
 
@@ -166,21 +164,26 @@ fn handle_firing(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut worm_commands: EventReader<WormCommand>,
     mut aiming_state: ResMut<AimingState>,
-    mut game_state: ResMut<GameState>,
+    game_state: Res<GameState>,
+    mut turn: TurnFlow,
     weapon_inventory: Res<WeaponInventory>,
-    worm_query: Query<&Transform, (With<Worm>, With<PlayerControlled>)>,
+    worm_query: Query<&Transform, (With<Worm>, With<ActiveWorm>)>,
 ) {
-    if !matches!(game_state.game_phase, GamePhase::Aiming) || !aiming_state.is_aiming {
+    if !matches!(turn.phase(), GamePhase::Aiming) || !aiming_state.is_aiming {
+        worm_commands.clear();
         return;
     }
     
-    // Fire when releasing Enter or pressing mouse
-    if keyboard_input.just_released(KeyCode::Enter) && aiming_state.power_charging {
+    // Fire when the charge is let go
+    let released = worm_commands.read()
+        .filter(|command| **command == WormCommand::Release)
+        .count() > 0;
+    if released && aiming_state.power_charging {
         if let Some(current_weapon) = weapon_inventory.weapons.get(weapon_inventory.current_weapon) {
             // Find active worm position
-            for worm_transform in worm_query.iter() {
+            if let Some(worm_transform) = worm_query.iter().next() {
                 let angle_rad = aiming_state.aim_angle.to_radians();
                 let direction = Vec2::new(angle_rad.cos(), angle_rad.sin());
                 
@@ -196,20 +199,19 @@ fn handle_firing(
                     &mut meshes,
                     &mut materials,
                     current_weapon.clone(),
+                    Some(game_state.current_player),
                     firing_position,
                     direction,
                     aiming_state.power,
                 );
                 
                 // Update game state
-                game_state.start_firing();
-                game_state.projectile_launched();
+                turn.projectile_launched();
                 
                 // Reset aiming state
                 aiming_state.is_aiming = false;
                 aiming_state.power_charging = false;
                 aiming_state.power = 0.5;
-                break;
             }
         }
     }
@@ -222,7 +224,7 @@ fn update_trajectory_preview(
     aiming_state: Res<AimingState>,
     weapon_inventory: Res<WeaponInventory>,
     wind: Res<WindSystem>,
-    worm_query: Query<&Transform, (With<Worm>, With<PlayerControlled>)>,
+    worm_query: Query<&Transform, (With<Worm>, With<PlayerControlled>, With<ActiveWorm>)>,
     preview_query: Query<Entity, With<TrajectoryPreview>>,
     crosshair_query: Query<Entity, With<AimingCrosshair>>,
     power_bar_query: Query<Entity, With<PowerBar>>,
@@ -258,12 +260,6 @@ fn update_trajectory_preview(
     let weapon_stats = current_weapon.get_stats();
     let angle_rad = aiming_state.aim_angle.to_radians();
     let direction = Vec2::new(angle_rad.cos(), angle_rad.sin());
-    
-    // Only recalculate trajectory if angle or power changed significantly
-    let current_weapon = weapon_inventory.weapons.get(weapon_inventory.current_weapon).unwrap();
-    let angle_rad = aiming_state.aim_angle.to_radians();
-    let direction = Vec2::new(angle_rad.cos(), angle_rad.sin());
-    let weapon_stats = current_weapon.get_stats();
     let initial_velocity = direction * aiming_state.power * weapon_stats.projectile_speed;
     
     let mut trajectory_points = Vec::new();
@@ -285,7 +281,6 @@ fn update_trajectory_preview(
             break;
         }
     }
-    }
     
     // Spawn trajectory preview dots
     for (i, point) in trajectory_points.iter().enumerate() {
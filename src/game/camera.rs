@@ -1,6 +1,9 @@
 use bevy::prelude::*;
-use crate::game::worm::Worm;
-use crate::game::game_state::GameState;
+use bevy::ecs::system::SystemParam;
+use bevy::window::PrimaryWindow;
+use crate::game::worm::{Worm, ActiveWorm};
+use crate::game::menu::AppState;
+use crate::game::game_state::GamePhase;
 
 pub struct CameraPlugin;
 
@@ -12,7 +15,8 @@ impl Plugin for CameraPlugin {
                 camera_follow_active_worm,
                 camera_manual_controls,
                 camera_zoom_controls,
-            ));
+            ).run_if(in_state(AppState::InGame)))
+            .add_systems(OnEnter(GamePhase::TurnTransition), return_to_active_worm);
     }
 }
 
@@ -39,21 +43,21 @@ impl Default for CameraController {
     }
 }
 
+/// Each new turn starts with the camera on the worm that's playing it.
+fn return_to_active_worm(mut camera_controller: ResMut<CameraController>) {
+    camera_controller.manual_control = false;
+}
+
 fn camera_follow_active_worm(
     mut camera_controller: ResMut<CameraController>,
-    _game_state: Res<GameState>,
-    worm_query: Query<&Transform, (With<Worm>, Without<Camera>)>,
+    worm_query: Query<&Transform, (With<Worm>, With<ActiveWorm>, Without<Camera>)>,
 ) {
     if camera_controller.manual_control {
         return;
     }
     
-    // Find the active player's worm
-    for transform in worm_query.iter() {
-        // For now, just follow any worm - in a full implementation,
-        // you'd match the worm's team to the current player
+    if let Some(transform) = worm_query.iter().next() {
         camera_controller.target_position = transform.translation;
-        break;
     }
 }
 
@@ -138,4 +142,20 @@ fn camera_zoom_controls(
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Where the mouse points in the world, if it is over the window.
+#[derive(SystemParam)]
+pub struct WorldCursor<'w, 's> {
+    window_query: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    camera_query: Query<'w, 's, (&'static Camera, &'static GlobalTransform)>,
+}
+
+impl WorldCursor<'_, '_> {
+    pub fn position(&self) -> Option<Vec2> {
+        let window = self.window_query.single().ok()?;
+        let cursor = window.cursor_position()?;
+        let (camera, camera_transform) = self.camera_query.iter().next()?;
+        camera.viewport_to_world_2d(camera_transform, cursor).ok()
+    }
+}
@@ -0,0 +1,360 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::game::replay::simulation_running;
+use crate::game::terrain::TerrainMap;
+use crate::game::worm::Worm;
+use crate::game::props::Prop;
+use crate::game::events::{ApplyDamage, ApplyHealing, DamageDealt};
+use crate::game::game_state::GamePhase;
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(DamageModel::default())
+            .add_systems(Update, (
+                apply_damage,
+                apply_healing,
+                count_down_pending_damage.run_if(in_state(GamePhase::TurnResolution)),
+                animate_damage_numbers,
+            ).run_if(simulation_running));
+    }
+}
+
+/// How explosion damage drops off from the blast center to its edge.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum FalloffCurve {
+    /// Full damage anywhere inside the radius
+    Constant,
+    /// Damage drops evenly with distance
+    #[default]
+    Linear,
+    /// Damage stays high near the center and drops quickly at the edge
+    Quadratic,
+    /// Smoothstep-shaped falloff
+    Smooth,
+}
+
+impl FalloffCurve {
+    /// Damage multiplier for a target at `t` = distance / radius, in `0.0..=1.0`.
+    pub fn factor(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            FalloffCurve::Constant => 1.0,
+            FalloffCurve::Linear => 1.0 - t,
+            FalloffCurve::Quadratic => 1.0 - t * t,
+            FalloffCurve::Smooth => 1.0 - t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Tunables for explosion damage. Read by `explosion_system` when a blast goes off;
+/// the falloff comes from the match's scheme.
+#[derive(Resource)]
+pub struct DamageModel {
+    pub falloff: FalloffCurve,
+    /// Whether terrain between the blast and the target soaks up damage
+    pub terrain_occlusion: bool,
+    /// Fraction of damage lost per solid pixel crossed
+    pub occlusion_per_pixel: f32,
+    /// Occluded targets always take at least this fraction of the damage
+    pub min_occlusion_factor: f32,
+}
+
+impl Default for DamageModel {
+    fn default() -> Self {
+        Self {
+            falloff: FalloffCurve::default(),
+            terrain_occlusion: true,
+            occlusion_per_pixel: 0.02,
+            min_occlusion_factor: 0.25,
+        }
+    }
+}
+
+impl DamageModel {
+    /// Damage dealt to a round target of `target_radius` at `target_center` by a blast.
+    /// Distance is measured to the nearest point of the target rather than its center.
+    pub fn explosion_damage(
+        &self,
+        terrain: &TerrainMap,
+        blast_center: Vec2,
+        blast_radius: f32,
+        max_damage: f32,
+        target_center: Vec2,
+        target_radius: f32,
+    ) -> f32 {
+        let offset = target_center - blast_center;
+        let surface_distance = (offset.length() - target_radius).max(0.0);
+
+        if surface_distance > blast_radius {
+            return 0.0;
+        }
+
+        let mut damage = max_damage * self.falloff.factor(surface_distance / blast_radius);
+
+        if self.terrain_occlusion && surface_distance > 0.0 {
+            let nearest_point = blast_center + offset.normalize_or_zero() * surface_distance;
+            let solid_pixels = count_solid_pixels(terrain, blast_center, nearest_point);
+            let occlusion = (1.0 - solid_pixels as f32 * self.occlusion_per_pixel)
+                .max(self.min_occlusion_factor);
+            damage *= occlusion;
+        }
+
+        damage
+    }
+}
+
+// Projectiles go off a few pixels into the ground they hit. Up to this many solid
+// pixels at the start of a ray are the blast's own seat, not terrain in the way.
+const BLAST_SEAT_DEPTH: u32 = 6;
+
+/// Counts solid terrain pixels on the straight line between a blast and a target,
+/// leaving out the ground the blast went off in.
+fn count_solid_pixels(terrain: &TerrainMap, from: Vec2, to: Vec2) -> u32 {
+    let half_size = Vec2::new(terrain.width as f32 / 2.0, terrain.height as f32 / 2.0);
+    let steps = from.distance(to).ceil() as u32;
+    let mut count = 0;
+    let mut in_seat = true;
+
+    for step in 0..=steps {
+        let t = if steps == 0 { 0.0 } else { step as f32 / steps as f32 };
+        let point = from.lerp(to, t) + half_size;
+        if terrain.is_solid(point.x as i32, point.y as i32) {
+            if in_seat && step < BLAST_SEAT_DEPTH {
+                continue;
+            }
+            count += 1;
+        }
+        in_seat = false;
+    }
+    count
+}
+
+/// Floating number shown over a worm while its damage is counted off.
+#[derive(Component)]
+pub struct DamageNumber {
+    pub lifetime: Timer,
+}
+
+// Health drains at this many points per second while the turn resolves
+const HEALTH_COUNT_DOWN_RATE: f32 = 50.0;
+
+/// The one place damage is taken. Everything that hurts sends `ApplyDamage`, and this
+/// reports what was actually taken through `DamageDealt`. Worm damage is only queued
+/// here; health drops when the turn resolves.
+fn apply_damage(
+    mut damage_events: EventReader<ApplyDamage>,
+    mut dealt_events: EventWriter<DamageDealt>,
+    mut worm_query: Query<&mut Worm>,
+    mut prop_query: Query<&mut Prop>,
+) {
+    for event in damage_events.read() {
+        if let Ok(mut worm) = worm_query.get_mut(event.target) {
+            // Can't take more damage than the health that will be left
+            let remaining_health = worm.health - worm.pending_damage;
+            if remaining_health <= 0.0 {
+                continue;
+            }
+
+            let taken = event.amount.min(remaining_health);
+            worm.pending_damage += taken;
+            worm.last_attacker = event.source_team;
+            worm.last_damage_cause = Some(event.cause.clone());
+
+            dealt_events.write(DamageDealt {
+                source_team: event.source_team,
+                target: event.target,
+                target_team: Some(worm.team),
+                amount: taken,
+                cause: event.cause.clone(),
+            });
+        } else if let Ok(mut prop) = prop_query.get_mut(event.target) {
+            if prop.health <= 0.0 {
+                continue;
+            }
+
+            let previous_health = prop.health;
+            prop.health = (prop.health - event.amount).max(0.0);
+
+            dealt_events.write(DamageDealt {
+                source_team: event.source_team,
+                target: event.target,
+                target_team: None,
+                amount: previous_health - prop.health,
+                cause: event.cause.clone(),
+            });
+        }
+    }
+}
+
+/// Health given back, up to the worm's maximum. Damage still being counted off this
+/// turn has already been taken, so only the health that will be left counts.
+fn apply_healing(
+    mut commands: Commands,
+    mut healing_events: EventReader<ApplyHealing>,
+    mut worm_query: Query<(&Transform, &mut Worm)>,
+) {
+    for event in healing_events.read() {
+        let Ok((transform, mut worm)) = worm_query.get_mut(event.target) else {
+            continue;
+        };
+
+        let remaining_health = worm.health - worm.pending_damage;
+        if remaining_health <= 0.0 {
+            continue;
+        }
+
+        let healed = event.amount.min(worm.max_health - remaining_health);
+        if healed <= 0.0 {
+            continue;
+        }
+
+        // Fill up to the maximum, then cancel out damage still to come off
+        let into_health = healed.min(worm.max_health - worm.health);
+        worm.health += into_health;
+        worm.pending_damage -= healed - into_health;
+
+        spawn_heal_number(&mut commands, transform.translation, healed);
+    }
+}
+
+fn count_down_pending_damage(
+    time: Res<Time>,
+    mut worm_query: Query<&mut Worm>,
+) {
+    for mut worm in worm_query.iter_mut() {
+        if worm.pending_damage <= 0.0 {
+            continue;
+        }
+
+        let step = worm.pending_damage.min(HEALTH_COUNT_DOWN_RATE * time.delta_secs());
+        worm.pending_damage -= step;
+        worm.health = (worm.health - step).max(0.0);
+
+        if worm.health <= 0.0 {
+            worm.pending_damage = 0.0;
+        }
+    }
+}
+
+pub fn spawn_damage_number(commands: &mut Commands, position: Vec3, amount: f32) {
+    spawn_floating_number(commands, position, format!("{:.0}", amount), Color::srgb(1.0, 0.3, 0.3));
+}
+
+pub fn spawn_heal_number(commands: &mut Commands, position: Vec3, amount: f32) {
+    spawn_floating_number(commands, position, format!("+{:.0}", amount), Color::srgb(0.3, 1.0, 0.3));
+}
+
+fn spawn_floating_number(commands: &mut Commands, position: Vec3, text: String, color: Color) {
+    commands.spawn((
+        Text2d::new(text),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(color),
+        Transform::from_translation(position + Vec3::new(0.0, 40.0, 2.0)),
+        DamageNumber {
+            lifetime: Timer::from_seconds(1.5, TimerMode::Once),
+        },
+    ));
+}
+
+fn animate_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut number_query: Query<(Entity, &mut Transform, &mut TextColor, &mut DamageNumber)>,
+) {
+    for (entity, mut transform, mut color, mut number) in number_query.iter_mut() {
+        number.lifetime.tick(time.delta());
+
+        if number.lifetime.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // Drift upward and fade out
+        transform.translation.y += 30.0 * time.delta_secs();
+        color.0.set_alpha(1.0 - number.lifetime.fraction());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [FalloffCurve; 4] = [
+        FalloffCurve::Constant,
+        FalloffCurve::Linear,
+        FalloffCurve::Quadratic,
+        FalloffCurve::Smooth,
+    ];
+
+    /// 64x64 pixels, solid below row 32 and in a wall at columns 40..45 above it.
+    fn test_map() -> TerrainMap {
+        let (width, height) = (64, 64);
+        let mut pixels = vec![false; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                pixels[y * width + x] = y < 32 || (40..45).contains(&x);
+            }
+        }
+        TerrainMap::from_pixels(width, height, pixels, vec![false; width * height]).unwrap()
+    }
+
+    /// World position of the middle of a pixel.
+    fn pixel(terrain: &TerrainMap, x: f32, y: f32) -> Vec2 {
+        Vec2::new(x + 0.5 - terrain.width as f32 / 2.0, y + 0.5 - terrain.height as f32 / 2.0)
+    }
+
+    #[test]
+    fn falloff_is_full_at_the_center_and_gone_at_the_edge() {
+        for curve in CURVES {
+            assert_eq!(curve.factor(0.0), 1.0, "{:?}", curve);
+            let expected_at_edge = if curve == FalloffCurve::Constant { 1.0 } else { 0.0 };
+            assert_eq!(curve.factor(1.0), expected_at_edge, "{:?}", curve);
+        }
+    }
+
+    #[test]
+    fn falloff_is_clamped_outside_the_radius() {
+        for curve in CURVES {
+            assert_eq!(curve.factor(-0.5), curve.factor(0.0), "{:?}", curve);
+            assert_eq!(curve.factor(3.0), curve.factor(1.0), "{:?}", curve);
+        }
+    }
+
+    #[test]
+    fn the_ground_a_blast_sits_in_doesnt_shield() {
+        let terrain = test_map();
+        let blast = pixel(&terrain, 10.0, 30.0);
+        let target = pixel(&terrain, 10.0, 50.0);
+
+        assert_eq!(count_solid_pixels(&terrain, blast, target), 0);
+
+        let damage = DamageModel::default().explosion_damage(&terrain, blast, 60.0, 40.0, target, 0.0);
+        let unshielded = 40.0 * FalloffCurve::Linear.factor(20.0 / 60.0);
+        assert!((damage - unshielded).abs() < 0.01, "{} against {}", damage, unshielded);
+    }
+
+    #[test]
+    fn ground_past_the_blast_seat_still_counts() {
+        let terrain = test_map();
+        // 20 rows deep, so the ray crosses 20 solid rows and only the first 6 are the seat
+        let blast = pixel(&terrain, 10.0, 12.0);
+        let target = pixel(&terrain, 10.0, 50.0);
+
+        assert_eq!(count_solid_pixels(&terrain, blast, target), 20 - BLAST_SEAT_DEPTH);
+    }
+
+    #[test]
+    fn terrain_between_blast_and_target_shields() {
+        let terrain = test_map();
+        let blast = pixel(&terrain, 30.0, 40.0);
+        let target = pixel(&terrain, 55.0, 40.0);
+
+        assert_eq!(count_solid_pixels(&terrain, blast, target), 5);
+    }
+}
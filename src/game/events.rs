@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::game::weapons::WeaponType;
+use crate::game::scheme::SuddenDeathEffect;
+
+/// Registers the gameplay events other plugins can subscribe to, and keeps a combat
+/// log of them at debug level.
+pub struct GameEventsPlugin;
+
+impl Plugin for GameEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<ApplyDamage>()
+            .add_event::<DamageDealt>()
+            .add_event::<ApplyHealing>()
+            .add_event::<WormDied>()
+            .add_event::<ProjectileFired>()
+            .add_event::<TerrainDestroyed>()
+            .add_event::<SuddenDeathStarted>()
+            .add_event::<WormCommand>()
+            .add_systems(Update, log_combat);
+    }
+}
+
+fn log_combat(
+    mut fired_events: EventReader<ProjectileFired>,
+    mut damage_events: EventReader<DamageDealt>,
+    mut died_events: EventReader<WormDied>,
+    mut terrain_events: EventReader<TerrainDestroyed>,
+) {
+    for event in fired_events.read() {
+        debug!("Team {:?} fired {:?} from {} at {}", event.team, event.weapon, event.position, event.velocity);
+    }
+    for event in damage_events.read() {
+        debug!("{:?} took {:.0} damage from team {:?} ({:?})", event.target, event.amount, event.source_team, event.cause);
+    }
+    for event in died_events.read() {
+        debug!("Worm {:?} of team {} was killed by team {:?} ({:?})", event.worm, event.team, event.killer_team, event.cause);
+    }
+    for event in terrain_events.read() {
+        debug!("Terrain destroyed at {} with radius {:.0}", event.center, event.radius);
+    }
+}
+
+/// What hurt a worm or prop.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DamageCause {
+    /// A weapon fired or placed by a worm
+    Weapon(WeaponType),
+    /// Explosions that don't come from a weapon, like barrels
+    Explosion,
+    Fire,
+    Fall,
+    Drowning,
+}
+
+/// Request to damage an entity. Only `apply_damage` turns these into health changes.
+#[derive(Event, Clone, Debug)]
+pub struct ApplyDamage {
+    pub target: Entity,
+    pub amount: f32,
+    pub source_team: Option<u32>,
+    pub cause: DamageCause,
+}
+
+/// Request to give a worm health back. Only `apply_healing` turns these into health.
+#[derive(Event, Clone, Debug)]
+pub struct ApplyHealing {
+    pub target: Entity,
+    pub amount: f32,
+}
+
+/// Damage that was actually taken, after clamping to the target's remaining health.
+#[derive(Event, Clone, Debug)]
+pub struct DamageDealt {
+    pub source_team: Option<u32>,
+    pub target: Entity,
+    /// `None` for props
+    pub target_team: Option<u32>,
+    pub amount: f32,
+    pub cause: DamageCause,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct WormDied {
+    pub worm: Entity,
+    pub team: u32,
+    pub killer_team: Option<u32>,
+    pub cause: DamageCause,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct ProjectileFired {
+    pub team: Option<u32>,
+    pub weapon: WeaponType,
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct TerrainDestroyed {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct SuddenDeathStarted {
+    pub effect: SuddenDeathEffect,
+}
+
+/// Something the team playing wants its worm to do. Keyboard, AI and anything else
+/// that plays a turn send these; the worm, weapon, tool and turn systems carry them
+/// out after `IssueWormCommands`, checking each against the current phase. Replays
+/// are a record of these.
+#[derive(Event, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum WormCommand {
+    /// Walk this frame, -1 for left to 1 for right; 0 stops the worm
+    Move(f32),
+    Jump,
+    /// Go into aiming, or back out of it
+    ToggleAim,
+    /// Turn the aim, in degrees, anticlockwise
+    AimBy(f32),
+    /// Start charging the shot
+    BeginCharge,
+    /// Fire the charged shot
+    Release,
+    /// Index into the weapon inventory
+    SelectWeapon(usize),
+    /// Put the selected placed weapon down in front of the worm
+    Place,
+    /// Set off the team's walking sheep
+    Detonate,
+    /// Use the selected strike or tool on this point of the map
+    Target(#[serde(with = "vec2_as_array")] Vec2),
+    /// Light the jetpack
+    TakeOff,
+    /// Let go of the rope, or switch the jetpack off
+    LetGo,
+    /// Up or down on a rope or jetpack this frame, 1 for up to -1 for down
+    Climb(f32),
+    /// Turn the girder about to be placed, in degrees
+    RotateGirder(f32),
+    EndTurn,
+}
+
+/// Written as `[x, y]`, like the positions in saves.
+mod vec2_as_array {
+    use bevy::math::Vec2;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(point: &Vec2, serializer: S) -> Result<S::Ok, S::Error> {
+        point.to_array().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec2, D::Error> {
+        <[f32; 2]>::deserialize(deserializer).map(Vec2::from_array)
+    }
+}
+
+/// The systems that send `WormCommand`s, so they are carried out on the same frame.
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IssueWormCommands;
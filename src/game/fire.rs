@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+use crate::game::replay::simulation_running;
+use crate::game::terrain::TerrainMap;
+use crate::game::worm::Worm;
+use crate::game::physics::Collider;
+use crate::game::events::{ApplyDamage, DamageCause, TerrainDestroyed};
+
+pub struct FirePlugin;
+
+impl Plugin for FirePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            fire_movement,
+            fire_erode_terrain,
+            fire_damage_worms,
+            burn_out_fires,
+        ).chain().run_if(simulation_running));
+    }
+}
+
+/// A burning blob of napalm. Falls until it lands on terrain, then sticks there,
+/// burns the ground away underneath it and hurts any worm standing in it.
+#[derive(Component)]
+pub struct Fire {
+    pub velocity: Vec2,
+    pub radius: f32,
+    pub damage_per_second: f32,
+    pub burn_timer: Timer,
+    pub erode_timer: Timer,
+    pub stuck: bool,
+    pub source_team: Option<u32>,
+}
+
+impl Fire {
+    pub fn new(velocity: Vec2, burn_time: f32, source_team: Option<u32>) -> Self {
+        Self {
+            velocity,
+            radius: 8.0,
+            damage_per_second: 12.0,
+            burn_timer: Timer::from_seconds(burn_time, TimerMode::Once),
+            erode_timer: Timer::from_seconds(0.4, TimerMode::Repeating),
+            stuck: false,
+            source_team,
+        }
+    }
+}
+
+const FIRE_GRAVITY: f32 = -600.0;
+const ERODE_RADIUS: f32 = 3.0;
+
+/// Scatters `count` burning particles from `position`, flung upward and outward.
+pub fn spawn_fire(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    rng: &mut fastrand::Rng,
+    position: Vec3,
+    count: usize,
+    source_team: Option<u32>,
+) {
+    for _ in 0..count {
+        let angle = std::f32::consts::FRAC_PI_4 + rng.f32() * std::f32::consts::FRAC_PI_2; // Mostly upward
+        let speed = rng.f32() * 150.0 + 50.0;
+        let velocity = Vec2::new(angle.cos() * speed, angle.sin() * speed);
+        let burn_time = rng.f32() * 3.0 + 3.0;
+        let color = Color::srgb(1.0, 0.4 + rng.f32() * 0.3, 0.0);
+        let offset = Vec3::new(
+            (rng.f32() - 0.5) * 10.0,
+            (rng.f32() - 0.5) * 10.0,
+            0.4,
+        );
+
+        spawn_flame(commands, meshes, materials, position + offset, color, Fire::new(velocity, burn_time, source_team));
+    }
+}
+
+/// Spawns a single flame. Used by `spawn_fire` and to put back the fires in a saved match.
+pub fn spawn_flame(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    position: Vec3,
+    color: Color,
+    fire: Fire,
+) -> Entity {
+    commands.spawn((
+        Mesh2d(meshes.add(bevy::math::primitives::Circle::new(5.0))),
+        MeshMaterial2d(materials.add(ColorMaterial::from(color))),
+        Transform::from_translation(position),
+        fire,
+    )).id()
+}
+
+fn fire_movement(
+    time: Res<Time>,
+    terrain: Res<TerrainMap>,
+    mut fire_query: Query<(&mut Transform, &mut Fire)>,
+) {
+    for (mut transform, mut fire) in fire_query.iter_mut() {
+        let world_x = transform.translation.x + (terrain.width as f32 / 2.0);
+        let world_y = transform.translation.y + (terrain.height as f32 / 2.0);
+
+        if fire.stuck {
+            // Start falling again once the ground underneath has burned away
+            if !terrain.check_collision(world_x, world_y - fire.radius * 0.5, fire.radius * 0.5) {
+                fire.stuck = false;
+                fire.velocity = Vec2::ZERO;
+            }
+            continue;
+        }
+
+        fire.velocity.y += FIRE_GRAVITY * time.delta_secs();
+        transform.translation.x += fire.velocity.x * time.delta_secs();
+        transform.translation.y += fire.velocity.y * time.delta_secs();
+
+        let world_x = transform.translation.x + (terrain.width as f32 / 2.0);
+        let world_y = transform.translation.y + (terrain.height as f32 / 2.0);
+
+        if terrain.check_collision(world_x, world_y, fire.radius * 0.5) {
+            fire.stuck = true;
+            fire.velocity = Vec2::ZERO;
+        }
+    }
+}
+
+fn fire_erode_terrain(
+    time: Res<Time>,
+    mut terrain: ResMut<TerrainMap>,
+    mut terrain_events: EventWriter<TerrainDestroyed>,
+    mut fire_query: Query<(&Transform, &mut Fire)>,
+) {
+    for (transform, mut fire) in fire_query.iter_mut() {
+        if !fire.stuck {
+            continue;
+        }
+
+        fire.erode_timer.tick(time.delta());
+        if fire.erode_timer.just_finished() {
+            // Eat away the ground just below the flame
+            let world_x = transform.translation.x + (terrain.width as f32 / 2.0);
+            let world_y = transform.translation.y + (terrain.height as f32 / 2.0);
+            terrain.destroy_circle(world_x, world_y - fire.radius * 0.5, ERODE_RADIUS);
+            terrain_events.write(TerrainDestroyed {
+                center: transform.translation.truncate() - Vec2::new(0.0, fire.radius * 0.5),
+                radius: ERODE_RADIUS,
+            });
+        }
+    }
+}
+
+fn fire_damage_worms(
+    time: Res<Time>,
+    mut damage_events: EventWriter<ApplyDamage>,
+    fire_query: Query<(&Transform, &Fire)>,
+    worm_query: Query<(Entity, &Transform, &Collider, &Worm)>,
+) {
+    for (worm_entity, worm_transform, collider, worm) in worm_query.iter() {
+        if worm.health <= 0.0 {
+            continue;
+        }
+
+        // Every flame the worm is standing in adds to the burn
+        for (fire_transform, fire) in fire_query.iter() {
+            let distance = fire_transform.translation.truncate()
+                .distance(worm_transform.translation.truncate());
+            if distance <= fire.radius + collider.radius {
+                damage_events.write(ApplyDamage {
+                    target: worm_entity,
+                    amount: fire.damage_per_second * time.delta_secs(),
+                    source_team: fire.source_team,
+                    cause: DamageCause::Fire,
+                });
+            }
+        }
+    }
+}
+
+fn burn_out_fires(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut fire_query: Query<(Entity, &Transform, &mut Fire, &MeshMaterial2d<ColorMaterial>)>,
+) {
+    for (entity, transform, mut fire, material_handle) in fire_query.iter_mut() {
+        fire.burn_timer.tick(time.delta());
+
+        // Fell off the map or burned out
+        if fire.burn_timer.finished() || transform.translation.y < -1000.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // Dim the flame as it burns down
+        if let Some(material) = materials.get_mut(&material_handle.0) {
+            let mut color = material.color;
+            color.set_alpha(1.0 - fire.burn_timer.fraction() * 0.7);
+            material.color = color;
+        }
+    }
+}
@@ -1,48 +1,194 @@
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
 use crate::game::worm::Worm;
+use crate::game::weapons::{Explosion, Projectile};
+use crate::game::physics::RigidBody;
+use crate::game::placed::Fuse;
+use crate::game::events::{DamageCause, WormDied, SuddenDeathStarted, WormCommand, IssueWormCommands};
+use crate::game::scheme::{GameScheme, MatchSettings, TeamSetup, TeamController};
+use crate::game::profiles::GravestoneStyle;
+use crate::game::menu::AppState;
+use crate::game::replay::{simulation_running, watching_replay};
+use crate::game::worm::ActiveWorm;
 
 pub struct GameStatePlugin;
 
 impl Plugin for GameStatePlugin {
     fn build(&self, app: &mut App) {
         app
-            .insert_resource(GameState::new())
-            .insert_resource(TurnTimer::new(30.0))
+            .add_sub_state::<GamePhase>()
+            .add_event::<PhaseRequest>()
+            .init_resource::<GameState>()
+            .init_resource::<TurnTimer>()
+            .init_resource::<RoundTimer>()
+            .insert_resource(TransitionTimer::default())
+            .insert_resource(TurnResolution::default())
+            .insert_resource(SettleRules::default())
+            // Only a new turn gets a full clock; coming back from aiming or a tool doesn't.
+            // `start_match` puts in a full one for the first turn.
+            .add_systems(
+                OnTransition { exited: GamePhase::TurnTransition, entered: GamePhase::PlayerTurn },
+                start_turn_timer,
+            )
+            .add_systems(OnEnter(GamePhase::ProjectileFlying), reset_settle_rules)
+            .add_systems(OnEnter(GamePhase::Retreat), start_retreat_timer)
+            .add_systems(OnEnter(GamePhase::TurnResolution), reset_turn_resolution)
+            .add_systems(OnEnter(GamePhase::TurnTransition), (release_active_worm, advance_to_next_team, start_sudden_death).chain())
+            .add_systems(OnEnter(GamePhase::GameOver), spawn_game_over_screen)
             .add_systems(Update, (
                 update_turn_timer,
-                handle_turn_end,
-                handle_turn_transition,
+                update_round_timer,
+                select_active_worm.run_if(in_state(GamePhase::PlayerTurn)),
+                wait_for_world_to_settle
+                    .run_if(in_state(GamePhase::ProjectileFlying).or(in_state(GamePhase::Explosion))),
+                handle_turn_end.after(IssueWormCommands),
+                resolve_turn.run_if(in_state(GamePhase::TurnResolution)),
+                handle_turn_transition.run_if(in_state(GamePhase::TurnTransition)),
                 update_active_player_indicator,
                 check_win_conditions,
-                handle_game_over,
-            ));
+                handle_game_over.run_if(in_state(GamePhase::GameOver).and(not(watching_replay))),
+            ).run_if(simulation_running))
+            // After every Update system has had its say
+            .add_systems(PostUpdate, apply_phase_requests.run_if(simulation_running));
     }
 }
 
 #[derive(Resource)]
 pub struct GameState {
     pub current_player: u32,
-    pub game_phase: GamePhase,
     pub teams: Vec<Team>,
-    pub winner: Option<u32>,
+    /// `None` while the match is still being played
+    pub winner: Option<MatchOutcome>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchOutcome {
+    Winner(u32),
+    /// The surviving teams were all in this alliance
+    Alliance(u32),
+    /// Every team was wiped out at once
+    Draw,
+}
+
+/// Where the current turn is. Only `apply_phase_requests` changes it; everything
+/// else asks for a new phase through `TurnFlow`. Restarting the match puts it back
+/// on `PlayerTurn`. Only exists while a match is on.
+#[derive(SubStates, Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[source(AppState = AppState::InGame)]
 pub enum GamePhase {
+    #[default]
     PlayerTurn,
     Aiming,
-    Firing,
     ProjectileFlying,
     Explosion,
+    /// A few seconds after a shot to walk or jump to cover; no more firing
+    Retreat,
+    UsingTool,
+    /// Damage taken during the turn is counted off and dead worms blow up
+    TurnResolution,
     TurnTransition,
     GameOver,
 }
 
+impl GamePhase {
+    /// Whether the turn flow allows going from this phase to `next`.
+    pub fn can_transition_to(&self, next: &GamePhase) -> bool {
+        use GamePhase::*;
+
+        match (self, next) {
+            (GameOver, _) => false,
+            (_, GameOver) => true,
+            (PlayerTurn, Aiming | UsingTool | ProjectileFlying | TurnResolution) => true,
+            (Aiming, PlayerTurn | ProjectileFlying) => true,
+            (ProjectileFlying, Explosion | Retreat) => true,
+            (Explosion, Retreat) => true,
+            (Retreat, TurnResolution) => true,
+            (UsingTool, PlayerTurn | TurnResolution) => true,
+            (TurnResolution, TurnTransition) => true,
+            (TurnTransition, PlayerTurn) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Asks for a change of phase. Invalid requests are logged and dropped.
+#[derive(Event, Clone, Debug)]
+pub struct PhaseRequest(pub GamePhase);
+
+/// The current phase plus a way to ask for the next one.
+#[derive(SystemParam)]
+pub struct TurnFlow<'w> {
+    phase: Res<'w, State<GamePhase>>,
+    requests: EventWriter<'w, PhaseRequest>,
+}
+
+impl TurnFlow<'_> {
+    pub fn phase(&self) -> &GamePhase {
+        self.phase.get()
+    }
+
+    pub fn request(&mut self, next: GamePhase) {
+        self.requests.write(PhaseRequest(next));
+    }
+
+    pub fn can_player_act(&self) -> bool {
+        matches!(self.phase(), GamePhase::PlayerTurn | GamePhase::Aiming)
+    }
+
+    /// Walking and jumping are also allowed while retreating.
+    pub fn can_player_move(&self) -> bool {
+        self.can_player_act() || *self.phase() == GamePhase::Retreat
+    }
+
+    pub fn start_aiming(&mut self) {
+        self.request(GamePhase::Aiming);
+    }
+
+    pub fn cancel_aiming(&mut self) {
+        self.request(GamePhase::PlayerTurn);
+    }
+
+    /// Rope and jetpack take over the worm's movement but keep the turn going.
+    pub fn start_tool_use(&mut self) {
+        self.request(GamePhase::UsingTool);
+    }
+
+    pub fn finish_tool_use(&mut self) {
+        // The turn may already have ended under the worm
+        if *self.phase() == GamePhase::UsingTool {
+            self.request(GamePhase::PlayerTurn);
+        }
+    }
+
+    pub fn projectile_launched(&mut self) {
+        self.request(GamePhase::ProjectileFlying);
+    }
+
+    /// Only the shot's own blasts move the turn along; barrels and mines going off
+    /// during someone's turn don't.
+    pub fn explosion_started(&mut self) {
+        if *self.phase() == GamePhase::ProjectileFlying {
+            self.request(GamePhase::Explosion);
+        }
+    }
+
+    /// Stops the turn. Damage taken during it is tallied before the next team goes.
+    pub fn end_turn(&mut self) {
+        self.request(GamePhase::TurnResolution);
+    }
+}
+
 #[derive(Clone)]
 pub struct Team {
     pub id: u32,
+    pub name: String,
     pub color: Color,
+    pub gravestone: GravestoneStyle,
+    pub controller: TeamController,
+    pub alliance: u32,
     pub worms_alive: u32,
+    /// Counts this team's turns so its worms take it in turns
+    pub turns_taken: u32,
 }
 
 #[derive(Resource)]
@@ -50,264 +196,568 @@ pub struct TurnTimer {
     pub max_time: f32,
     pub current_time: f32,
     pub is_active: bool,
+    /// Seconds of retreat after firing. Zero ends the turn as soon as the shot resolves.
+    pub retreat_time: f32,
 }
 
-impl GameState {
-    pub fn new() -> Self {
+/// Clock for the whole match. Sudden death starts at the next turn change once it
+/// runs out or enough rounds have been played.
+#[derive(Resource)]
+pub struct RoundTimer {
+    pub time_left: f32,
+    pub rounds_played: u32,
+    pub sudden_death: bool,
+}
+
+impl FromWorld for RoundTimer {
+    fn from_world(world: &mut World) -> Self {
+        Self::new(world.resource::<GameScheme>().round_time)
+    }
+}
+
+impl RoundTimer {
+    pub fn new(round_time: f32) -> Self {
         Self {
-            current_player: 0,
-            game_phase: GamePhase::PlayerTurn,
-            teams: vec![
-                Team { id: 0, color: Color::srgb(0.2, 0.8, 0.2), worms_alive: 1 },
-                Team { id: 1, color: Color::srgb(0.8, 0.2, 0.2), worms_alive: 1 },
-            ],
-            winner: None,
+            time_left: round_time,
+            rounds_played: 0,
+            sudden_death: false,
         }
     }
-    
-    pub fn can_player_act(&self) -> bool {
-        matches!(self.game_phase, GamePhase::PlayerTurn | GamePhase::Aiming)
+}
+
+/// Brief pause between one team's turn and the next.
+#[derive(Resource)]
+pub struct TransitionTimer(pub Timer);
+
+impl Default for TransitionTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(1.0, TimerMode::Once))
     }
-    
-    pub fn start_aiming(&mut self) {
-        if self.game_phase == GamePhase::PlayerTurn {
-            self.game_phase = GamePhase::Aiming;
+}
+
+/// When a shot counts as finished: nothing left to go off and nothing still moving.
+#[derive(Resource)]
+pub struct SettleRules {
+    /// Bodies slower than this count as at rest (pixels per second)
+    pub velocity_threshold: f32,
+    /// How long everything has to stay at rest
+    pub calm_time: f32,
+    /// Give up waiting after this long, even if something is still rolling
+    pub max_wait: f32,
+    pub waited: f32,
+    pub calm_for: f32,
+}
+
+impl Default for SettleRules {
+    fn default() -> Self {
+        Self {
+            velocity_threshold: 40.0,
+            calm_time: 0.3,
+            max_wait: 10.0,
+            waited: 0.0,
+            calm_for: 0.0,
         }
     }
-    
-    pub fn start_firing(&mut self) {
-        if self.game_phase == GamePhase::Aiming {
-            self.game_phase = GamePhase::Firing;
+}
+
+// Bodies below this have fallen off the map and will never settle
+const FELL_OFF_MAP_Y: f32 = -1000.0;
+
+/// Progress through the end-of-turn damage tally.
+#[derive(Resource)]
+pub struct TurnResolution {
+    /// Whether damage numbers have been shown for the damage now being counted
+    pub numbers_shown: bool,
+    /// Pause before each worm death so they play out one at a time
+    pub death_delay: Timer,
+}
+
+impl Default for TurnResolution {
+    fn default() -> Self {
+        Self {
+            numbers_shown: false,
+            death_delay: Timer::from_seconds(0.6, TimerMode::Once),
         }
     }
-    
-    pub fn projectile_launched(&mut self) {
-        if self.game_phase == GamePhase::Firing {
-            self.game_phase = GamePhase::ProjectileFlying;
-        }
+}
+
+impl FromWorld for GameState {
+    fn from_world(world: &mut World) -> Self {
+        Self::new(&world.resource::<MatchSettings>().teams)
     }
-    
-    pub fn explosion_started(&mut self) {
-        if self.game_phase == GamePhase::ProjectileFlying {
-            self.game_phase = GamePhase::Explosion;
+}
+
+impl GameState {
+    pub fn new(setups: &[TeamSetup]) -> Self {
+        Self {
+            current_player: 0,
+            teams: setups.iter().enumerate()
+                .map(|(id, setup)| Team {
+                    id: id as u32,
+                    name: setup.name.clone(),
+                    color: setup.color(),
+                    gravestone: setup.gravestone,
+                    controller: setup.controller,
+                    alliance: setup.alliance,
+                    worms_alive: 0,
+                    turns_taken: 0,
+                })
+                .collect(),
+            winner: None,
         }
     }
-    
-    pub fn end_turn(&mut self) {
-        self.game_phase = GamePhase::TurnTransition;
-        self.current_player = (self.current_player + 1) % self.teams.len() as u32;
-        // After a brief transition, return to PlayerTurn
-    }
-    
-    pub fn start_new_turn(&mut self) {
-        self.game_phase = GamePhase::PlayerTurn;
+
+    /// Passes the turn to the next team that still has worms, skipping eliminated ones.
+    /// Returns true when the turn order wrapped round to the start.
+    pub fn advance_player(&mut self) -> bool {
+        let team_count = self.teams.len() as u32;
+        if team_count == 0 {
+            return false;
+        }
+
+        let previous = self.current_player;
+        for step in 1..=team_count {
+            let candidate = (previous + step) % team_count;
+            if self.teams[candidate as usize].worms_alive > 0 {
+                self.current_player = candidate;
+                return candidate <= previous;
+            }
+        }
+
+        // Nobody left; win detection ends the match
+        self.current_player = (previous + 1) % team_count;
+        self.current_player <= previous
     }
-    
-    pub fn next_turn(&mut self) {
-        self.current_player = (self.current_player + 1) % self.teams.len() as u32;
-        self.game_phase = GamePhase::PlayerTurn;
+
+    pub fn count_worms_alive<'a>(&mut self, worms: impl Iterator<Item = &'a Worm>) {
+        for team in self.teams.iter_mut() {
+            team.worms_alive = 0;
+        }
+        for worm in worms.filter(|worm| worm.health > 0.0) {
+            if let Some(team) = self.teams.get_mut(worm.team as usize) {
+                team.worms_alive += 1;
+            }
+        }
     }
-    
+
     pub fn get_current_team(&self) -> Option<&Team> {
         self.teams.iter().find(|team| team.id == self.current_player)
     }
-    
-    pub fn check_win_condition(&mut self, worm_query: &Query<&Worm>) {
-        // Count alive worms per team
-        let mut team_counts = vec![0u32; self.teams.len()];
-        
-        for worm in worm_query.iter() {
-            if worm.health > 0.0 {
-                team_counts[worm.team as usize] += 1;
-            }
-        }
-        
-        // Update team alive counts
-        for (i, team) in self.teams.iter_mut().enumerate() {
-            team.worms_alive = team_counts[i];
+
+    pub fn team_name(&self, team: u32) -> String {
+        self.teams.get(team as usize)
+            .map(|team| team.name.clone())
+            .unwrap_or_else(|| format!("Team {}", team + 1))
+    }
+
+    /// Teams in the same alliance don't count as enemies. A team is its own ally.
+    pub fn are_allies(&self, a: u32, b: u32) -> bool {
+        match (self.teams.get(a as usize), self.teams.get(b as usize)) {
+            (Some(a), Some(b)) => a.alliance == b.alliance,
+            _ => a == b,
         }
-        
-        // Check for winner
-        let alive_teams: Vec<_> = team_counts.iter().enumerate()
-            .filter(|(_, &count)| count > 0)
-            .collect();
-            
-        if alive_teams.len() == 1 {
-            self.winner = Some(alive_teams[0].0 as u32);
-            self.game_phase = GamePhase::GameOver;
+    }
+
+    pub fn outcome_text(&self) -> String {
+        match self.winner {
+            Some(MatchOutcome::Winner(team)) => format!("{} Wins!", self.team_name(team)),
+            Some(MatchOutcome::Alliance(alliance)) => {
+                let names: Vec<_> = self.teams.iter()
+                    .filter(|team| team.alliance == alliance)
+                    .map(|team| team.name.clone())
+                    .collect();
+                format!("{} Win!", names.join(" & "))
+            }
+            Some(MatchOutcome::Draw) => "Draw!".to_string(),
+            None => String::new(),
         }
     }
 }
 
+impl FromWorld for TurnTimer {
+    fn from_world(world: &mut World) -> Self {
+        let scheme = world.resource::<GameScheme>();
+        Self::new(scheme.turn_time, scheme.retreat_time)
+    }
+}
+
 impl TurnTimer {
-    pub fn new(max_time: f32) -> Self {
+    pub fn new(max_time: f32, retreat_time: f32) -> Self {
         Self {
             max_time,
             current_time: max_time,
             is_active: true,
+            retreat_time,
         }
     }
-    
+
     pub fn reset(&mut self) {
         self.current_time = self.max_time;
         self.is_active = true;
     }
-    
+
+    /// Replaces what is left of the turn with the retreat time.
+    pub fn start_retreat(&mut self) {
+        self.current_time = self.retreat_time;
+        self.is_active = true;
+    }
+
     pub fn pause(&mut self) {
         self.is_active = false;
     }
-    
+
     pub fn resume(&mut self) {
         self.is_active = true;
     }
-    
+
     pub fn is_expired(&self) -> bool {
         self.current_time <= 0.0
     }
-    
-    pub fn time_remaining_ratio(&self) -> f32 {
-        (self.current_time / self.max_time).clamp(0.0, 1.0)
+}
+
+/// The one place the phase changes. Requests are checked in the order they were
+/// made, so a system can step through several phases in the same frame.
+pub fn apply_phase_requests(
+    mut requests: EventReader<PhaseRequest>,
+    phase: Res<State<GamePhase>>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+) {
+    let mut current = phase.get().clone();
+    let mut changed = false;
+
+    for PhaseRequest(next) in requests.read() {
+        if *next == current {
+            continue;
+        }
+
+        if current.can_transition_to(next) {
+            current = next.clone();
+            changed = true;
+        } else {
+            warn!("Rejected phase change from {:?} to {:?}", current, next);
+        }
+    }
+
+    if changed {
+        next_phase.set(current);
     }
 }
 
+fn start_turn_timer(mut timer: ResMut<TurnTimer>) {
+    timer.reset();
+}
+
+fn start_retreat_timer(mut timer: ResMut<TurnTimer>) {
+    timer.start_retreat();
+}
+
+fn reset_settle_rules(mut rules: ResMut<SettleRules>) {
+    rules.waited = 0.0;
+    rules.calm_for = 0.0;
+}
+
+fn reset_turn_resolution(mut resolution: ResMut<TurnResolution>) {
+    resolution.numbers_shown = false;
+    resolution.death_delay.reset();
+}
+
+/// Picks which of the current team's worms plays this turn, going round them in order.
+fn select_active_worm(
+    mut commands: Commands,
+    mut game_state: ResMut<GameState>,
+    active_query: Query<(), With<ActiveWorm>>,
+    worm_query: Query<(Entity, &Worm)>,
+) {
+    if !active_query.is_empty() {
+        return;
+    }
+
+    let current_player = game_state.current_player;
+    let mut candidates: Vec<Entity> = worm_query.iter()
+        .filter(|(_, worm)| worm.team == current_player && worm.health > 0.0)
+        .map(|(entity, _)| entity)
+        .collect();
+    if candidates.is_empty() {
+        return;
+    }
+    candidates.sort();
+
+    let Some(team) = game_state.teams.iter_mut().find(|team| team.id == current_player) else {
+        return;
+    };
+    let worm = candidates[team.turns_taken as usize % candidates.len()];
+    team.turns_taken += 1;
+    commands.entity(worm).insert(ActiveWorm);
+}
+
+fn release_active_worm(
+    mut commands: Commands,
+    active_query: Query<Entity, With<ActiveWorm>>,
+) {
+    for entity in active_query.iter() {
+        commands.entity(entity).remove::<ActiveWorm>();
+    }
+}
+
+/// The only place the turn passes to the next team.
+fn advance_to_next_team(
+    mut game_state: ResMut<GameState>,
+    mut round_timer: ResMut<RoundTimer>,
+    mut transition_timer: ResMut<TransitionTimer>,
+    worm_query: Query<&Worm>,
+) {
+    // Teams wiped out during the tally lose their turn
+    game_state.count_worms_alive(worm_query.iter());
+
+    // Back round to the start of the order means everyone has had a go
+    if game_state.advance_player() {
+        round_timer.rounds_played += 1;
+    }
+    transition_timer.0.reset();
+}
+
+fn update_round_timer(
+    time: Res<Time>,
+    phase: Res<State<GamePhase>>,
+    mut round_timer: ResMut<RoundTimer>,
+) {
+    if *phase.get() != GamePhase::GameOver {
+        round_timer.time_left = (round_timer.time_left - time.delta_secs()).max(0.0);
+    }
+}
+
+fn start_sudden_death(
+    scheme: Res<GameScheme>,
+    mut round_timer: ResMut<RoundTimer>,
+    mut sudden_death_events: EventWriter<SuddenDeathStarted>,
+    mut worm_query: Query<&mut Worm>,
+) {
+    if round_timer.sudden_death {
+        return;
+    }
+
+    let out_of_time = round_timer.time_left <= 0.0;
+    let out_of_rounds = scheme.rounds_until_sudden_death > 0
+        && round_timer.rounds_played >= scheme.rounds_until_sudden_death;
+    if !out_of_time && !out_of_rounds {
+        return;
+    }
+
+    round_timer.sudden_death = true;
+    info!("Sudden death!");
+
+    if scheme.sudden_death.sets_one_health() {
+        for mut worm in worm_query.iter_mut() {
+            if worm.health > 0.0 {
+                worm.health = 1.0;
+                worm.pending_damage = 0.0;
+            }
+        }
+    }
+
+    sudden_death_events.write(SuddenDeathStarted {
+        effect: scheme.sudden_death,
+    });
+}
+
 fn update_turn_timer(
     time: Res<Time>,
     mut timer: ResMut<TurnTimer>,
-    game_state: Res<GameState>,
+    phase: Res<State<GamePhase>>,
 ) {
-    if timer.is_active && game_state.game_phase == GamePhase::PlayerTurn {
+    if timer.is_active && matches!(phase.get(), GamePhase::PlayerTurn | GamePhase::UsingTool | GamePhase::Retreat) {
         timer.current_time -= time.delta_secs();
         timer.current_time = timer.current_time.max(0.0);
     }
 }
 
+/// Moves on to the retreat once the shot has fully played out.
+fn wait_for_world_to_settle(
+    time: Res<Time>,
+    mut turn: TurnFlow,
+    mut rules: ResMut<SettleRules>,
+    projectile_query: Query<(), With<Projectile>>,
+    explosion_query: Query<(), With<Explosion>>,
+    fuse_query: Query<(), With<Fuse>>,
+    body_query: Query<(&Transform, &RigidBody)>,
+) {
+    rules.waited += time.delta_secs();
+
+    let nothing_pending = projectile_query.is_empty()
+        && explosion_query.is_empty()
+        && fuse_query.is_empty();
+    let nothing_moving = body_query.iter().all(|(transform, body)| {
+        transform.translation.y < FELL_OFF_MAP_Y || body.velocity.length() < rules.velocity_threshold
+    });
+
+    if nothing_pending && nothing_moving {
+        rules.calm_for += time.delta_secs();
+    } else {
+        rules.calm_for = 0.0;
+    }
+
+    if rules.calm_for >= rules.calm_time || rules.waited >= rules.max_wait {
+        turn.request(GamePhase::Retreat);
+    }
+}
+
 fn handle_turn_end(
-    mut game_state: ResMut<GameState>,
-    mut timer: ResMut<TurnTimer>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    worm_query: Query<&Worm>,
+    mut turn: TurnFlow,
+    timer: Res<TurnTimer>,
+    mut worm_commands: EventReader<WormCommand>,
 ) {
-    let should_end_turn = timer.is_expired() 
-        || keyboard_input.just_pressed(KeyCode::Enter)
-        || keyboard_input.just_pressed(KeyCode::Tab);
-    
-    if should_end_turn && game_state.game_phase == GamePhase::PlayerTurn {
-        game_state.check_win_condition(&worm_query);
-        
-        if game_state.winner.is_none() {
-            game_state.next_turn();
-            timer.reset();
-        }
+    // Only the team playing sends commands, so it is the only one that can end its turn early
+    let ended_by_player = worm_commands.read()
+        .filter(|command| **command == WormCommand::EndTurn)
+        .count() > 0;
+    let should_end_turn = timer.is_expired() || ended_by_player;
+
+    if should_end_turn && matches!(turn.phase(), GamePhase::PlayerTurn | GamePhase::UsingTool | GamePhase::Retreat) {
+        turn.end_turn();
     }
 }
 
-fn handle_turn_transition(
-    mut game_state: ResMut<GameState>,
-    mut timer: ResMut<TurnTimer>,
+/// Counts down the turn's damage, then kills off dead worms one at a time.
+/// Death blasts can hurt other worms, which sends the tally round again.
+fn resolve_turn(
+    mut commands: Commands,
+    mut turn: TurnFlow,
+    mut resolution: ResMut<TurnResolution>,
     time: Res<Time>,
+    mut died_events: EventWriter<WormDied>,
+    explosion_query: Query<(), With<Explosion>>,
+    worm_query: Query<(Entity, &Transform, &Worm)>,
 ) {
-    if game_state.game_phase == GamePhase::TurnTransition {
-        // Brief pause between turns
-        static mut TRANSITION_TIME: f32 = 0.0;
-        unsafe {
-            TRANSITION_TIME += time.delta_secs();
-            if TRANSITION_TIME >= 1.0 {
-                TRANSITION_TIME = 0.0;
-                game_state.start_new_turn();
-                timer.reset();
+    // Let blasts finish before counting what they did
+    if !explosion_query.is_empty() {
+        return;
+    }
+
+    if !resolution.numbers_shown {
+        for (_, transform, worm) in worm_query.iter() {
+            if worm.pending_damage > 0.0 {
+                crate::game::damage::spawn_damage_number(&mut commands, transform.translation, worm.pending_damage);
             }
         }
+        resolution.numbers_shown = true;
+    }
+
+    // Health is still counting down
+    if worm_query.iter().any(|(_, _, worm)| worm.pending_damage > 0.0) {
+        return;
+    }
+
+    resolution.death_delay.tick(time.delta());
+    if !resolution.death_delay.finished() {
+        return;
+    }
+    resolution.death_delay.reset();
+
+    if let Some((entity, _, worm)) = worm_query.iter().find(|(_, _, worm)| worm.health <= 0.0) {
+        died_events.write(WormDied {
+            worm: entity,
+            team: worm.team,
+            killer_team: worm.last_attacker,
+            cause: worm.last_damage_cause.clone().unwrap_or(DamageCause::Explosion),
+        });
+
+        // Show whatever the death blast does
+        resolution.numbers_shown = false;
+        return;
+    }
+
+    turn.request(GamePhase::TurnTransition);
+}
+
+fn handle_turn_transition(
+    mut turn: TurnFlow,
+    mut transition_timer: ResMut<TransitionTimer>,
+    time: Res<Time>,
+) {
+    transition_timer.0.tick(time.delta());
+    if transition_timer.0.just_finished() {
+        turn.request(GamePhase::PlayerTurn);
     }
 }
 
 fn check_win_conditions(
     mut game_state: ResMut<GameState>,
+    mut turn: TurnFlow,
     worm_query: Query<&Worm>,
 ) {
-    if game_state.game_phase == GamePhase::GameOver {
+    // Deaths only count once the turn's damage has been tallied
+    if matches!(turn.phase(), GamePhase::GameOver | GamePhase::TurnResolution) {
         return;
     }
-    
-    // Count alive worms per team
-    let mut team_counts = vec![0u32; game_state.teams.len()];
-    
-    for worm in worm_query.iter() {
-        if worm.health > 0.0 {
-            team_counts[worm.team as usize] += 1;
-        }
-    }
-    
-    // Update team alive counts
-    for (i, team) in game_state.teams.iter_mut().enumerate() {
-        team.worms_alive = team_counts[i];
-    }
-    
-    // Check for winner
-    let alive_teams: Vec<_> = team_counts.iter().enumerate()
-        .filter(|(_, &count)| count > 0)
+
+    game_state.count_worms_alive(worm_query.iter());
+
+    // The match is over once everyone left standing is on the same side
+    let alive_teams: Vec<&Team> = game_state.teams.iter()
+        .filter(|team| team.worms_alive > 0)
         .collect();
-        
-    if alive_teams.len() <= 1 {
-        if let Some((team_id, _)) = alive_teams.first() {
-            game_state.winner = Some(*team_id as u32);
+
+    let outcome = match alive_teams.as_slice() {
+        [] => MatchOutcome::Draw,
+        [team] => MatchOutcome::Winner(team.id),
+        [first, rest @ ..] if rest.iter().all(|team| team.alliance == first.alliance) => {
+            MatchOutcome::Alliance(first.alliance)
         }
-        game_state.game_phase = GamePhase::GameOver;
-    }
+        _ => return,
+    };
+
+    game_state.winner = Some(outcome);
+    turn.request(GamePhase::GameOver);
 }
 
-fn handle_game_over(
+fn spawn_game_over_screen(
     mut commands: Commands,
     game_state: Res<GameState>,
+) {
+    let winner_text = game_state.outcome_text();
+
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            position_type: PositionType::Absolute,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+        crate::game::ui::GameOverUI,
+    )).with_children(|parent| {
+        parent.spawn((
+            Text::new(winner_text),
+            TextFont {
+                font_size: 48.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 1.0, 0.0)),
+        ));
+
+        parent.spawn((
+            Text::new("Press R to Restart, Enter for Results"),
+            TextFont {
+                font_size: 24.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+    });
+}
+
+fn handle_game_over(
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    game_over_query: Query<Entity, With<crate::game::ui::GameOverUI>>,
+    mut restart_events: EventWriter<crate::game::lifecycle::RestartMatch>,
+    mut next_state: ResMut<NextState<AppState>>,
 ) {
-    if game_state.game_phase == GamePhase::GameOver {
-        // Spawn game over UI if not already present
-        if game_over_query.is_empty() {
-            let winner_text = if let Some(winner) = game_state.winner {
-                format!("Player {} Wins!", winner + 1)
-            } else {
-                "Draw!".to_string()
-            };
-            
-            commands.spawn((
-                Node {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
-                    position_type: PositionType::Absolute,
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    flex_direction: FlexDirection::Column,
-                    ..default()
-                },
-                crate::game::ui::GameOverUI,
-            )).with_children(|parent| {
-                parent.spawn((
-                    Text::new(winner_text),
-                    TextFont {
-                        font_size: 48.0,
-                        ..default()
-                    },
-                    TextColor(Color::YELLOW),
-                ));
-                
-                parent.spawn((
-                    Text::new("Press R to Restart"),
-                    TextFont {
-                        font_size: 24.0,
-                        ..default()
-                    },
-                    TextColor(Color::WHITE),
-                ));
-            });
-        }
-        
-        // Handle restart
-        if keyboard_input.just_pressed(KeyCode::KeyR) {
-            // Reset game state (we'll implement proper restart later)
-            println!("Restart requested - reload the page for now");
-        }
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        restart_events.write(crate::game::lifecycle::RestartMatch { new_map: true });
+    } else if keyboard_input.just_pressed(KeyCode::Enter) {
+        next_state.set(AppState::Results);
     }
 }
 
@@ -320,7 +770,7 @@ fn update_active_player_indicator(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     indicator_query: Query<Entity, With<PlayerIndicator>>,
-    worm_query: Query<(&Transform, &Worm)>,
+    worm_query: Query<(&Transform, &Worm), With<ActiveWorm>>,
 ) {
     // Remove existing indicators
     for entity in indicator_query.iter() {
@@ -344,4 +794,110 @@ fn update_active_player_indicator(
             }
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::profiles::TeamProfiles;
+
+    const ALL_PHASES: [GamePhase; 9] = [
+        GamePhase::PlayerTurn,
+        GamePhase::Aiming,
+        GamePhase::ProjectileFlying,
+        GamePhase::Explosion,
+        GamePhase::Retreat,
+        GamePhase::UsingTool,
+        GamePhase::TurnResolution,
+        GamePhase::TurnTransition,
+        GamePhase::GameOver,
+    ];
+
+    /// A line-up with this many worms left in each team.
+    fn game_with_worms(worms_alive: &[u32]) -> GameState {
+        let profiles = TeamProfiles { profiles: Vec::new() };
+        let setups: Vec<TeamSetup> = (0..worms_alive.len())
+            .map(|index| TeamSetup::for_slot(index, &profiles, TeamController::AI))
+            .collect();
+        let mut game_state = GameState::new(&setups);
+        for (team, &alive) in game_state.teams.iter_mut().zip(worms_alive) {
+            team.worms_alive = alive;
+        }
+        game_state
+    }
+
+    #[test]
+    fn a_turn_can_go_every_way_it_plays_out() {
+        use GamePhase::*;
+        let allowed = [
+            (PlayerTurn, Aiming),
+            (PlayerTurn, UsingTool),
+            (PlayerTurn, ProjectileFlying),
+            (PlayerTurn, TurnResolution),
+            (Aiming, PlayerTurn),
+            (Aiming, ProjectileFlying),
+            (ProjectileFlying, Explosion),
+            (ProjectileFlying, Retreat),
+            (Explosion, Retreat),
+            (Retreat, TurnResolution),
+            (UsingTool, PlayerTurn),
+            (UsingTool, TurnResolution),
+            (TurnResolution, TurnTransition),
+            (TurnTransition, PlayerTurn),
+        ];
+        for (from, to) in allowed {
+            assert!(from.can_transition_to(&to), "{:?} to {:?}", from, to);
+        }
+    }
+
+    #[test]
+    fn a_turn_cant_skip_ahead_or_go_back() {
+        use GamePhase::*;
+        let denied = [
+            (PlayerTurn, Explosion),
+            (PlayerTurn, TurnTransition),
+            (Aiming, Retreat),
+            (Aiming, UsingTool),
+            (ProjectileFlying, PlayerTurn),
+            (ProjectileFlying, TurnResolution),
+            (Explosion, PlayerTurn),
+            (Retreat, PlayerTurn),
+            (UsingTool, ProjectileFlying),
+            (TurnResolution, PlayerTurn),
+            (TurnTransition, Aiming),
+        ];
+        for (from, to) in denied {
+            assert!(!from.can_transition_to(&to), "{:?} to {:?}", from, to);
+        }
+    }
+
+    #[test]
+    fn the_match_can_end_from_any_phase_but_not_restart_through_the_flow() {
+        for phase in ALL_PHASES {
+            if phase != GamePhase::GameOver {
+                assert!(phase.can_transition_to(&GamePhase::GameOver), "{:?}", phase);
+            }
+            assert!(!GamePhase::GameOver.can_transition_to(&phase), "{:?}", phase);
+        }
+    }
+
+    #[test]
+    fn advance_player_skips_eliminated_teams() {
+        let mut game_state = game_with_worms(&[1, 0, 2, 0]);
+
+        assert!(!game_state.advance_player());
+        assert_eq!(game_state.current_player, 2);
+
+        // Past the last team, round to the first
+        assert!(game_state.advance_player());
+        assert_eq!(game_state.current_player, 0);
+    }
+
+    #[test]
+    fn advance_player_moves_on_when_nobody_is_left() {
+        let mut game_state = game_with_worms(&[0, 0, 0]);
+        game_state.current_player = 2;
+
+        assert!(game_state.advance_player());
+        assert_eq!(game_state.current_player, 0);
+    }
+}
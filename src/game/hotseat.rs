@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use crate::game::game_state::{GameState, GamePhase, TurnTimer};
+use crate::game::replay::{simulation_running, ReplayPlayback, ReplayRecorder};
+use crate::game::scheme::TeamController;
+
+/// Several people sharing one keyboard. Input only reaches the team whose turn it is,
+/// and the game waits for the device to change hands between human teams.
+pub struct HotSeatPlugin;
+
+impl Plugin for HotSeatPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<HandOver>()
+            // Runs after the turn clock is reset, which happens on the transition
+            .add_systems(OnEnter(GamePhase::PlayerTurn), begin_hand_over)
+            // A restart during a turn starts the first one without entering the phase again
+            .add_systems(OnTransition { exited: GamePhase::PlayerTurn, entered: GamePhase::PlayerTurn }, begin_hand_over)
+            // After the input systems, so the key that dismisses the screen isn't also played
+            .add_systems(PostUpdate, dismiss_hand_over.run_if(simulation_running));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct HandOver {
+    /// Waiting for the next player to take the keyboard
+    pub waiting: bool,
+    /// The team that had the previous turn
+    pub last_team: Option<u32>,
+}
+
+#[derive(Component)]
+pub struct HandOverScreen;
+
+/// Run condition for systems that read the keyboard or mouse on behalf of the team playing.
+pub fn local_input_allowed(game_state: Res<GameState>, hand_over: Res<HandOver>) -> bool {
+    !hand_over.waiting
+        && game_state.get_current_team()
+            .is_some_and(|team| team.controller == TeamController::Human)
+}
+
+fn begin_hand_over(
+    mut commands: Commands,
+    mut hand_over: ResMut<HandOver>,
+    mut timer: ResMut<TurnTimer>,
+    game_state: Res<GameState>,
+) {
+    let Some(team) = game_state.get_current_team() else {
+        return;
+    };
+
+    // Coming back from aiming or a tool is still the same turn
+    if hand_over.last_team == Some(team.id) {
+        return;
+    }
+    hand_over.last_team = Some(team.id);
+
+    let human_teams = game_state.teams.iter()
+        .filter(|team| team.controller == TeamController::Human)
+        .count();
+    if team.controller != TeamController::Human || human_teams < 2 {
+        return;
+    }
+
+    hand_over.waiting = true;
+    timer.pause();
+
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            position_type: PositionType::Absolute,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.9)),
+        HandOverScreen,
+    )).with_children(|parent| {
+        parent.spawn((
+            Text::new(format!("Pass to {}", team.name)),
+            TextFont {
+                font_size: 48.0,
+                ..default()
+            },
+            TextColor(team.color),
+        ));
+
+        parent.spawn((
+            Text::new("Press Space when ready"),
+            TextFont {
+                font_size: 24.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+    });
+}
+
+fn dismiss_hand_over(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut hand_over: ResMut<HandOver>,
+    mut timer: ResMut<TurnTimer>,
+    recorder: Option<ResMut<ReplayRecorder>>,
+    playback: Option<Res<ReplayPlayback>>,
+    screen_query: Query<Entity, With<HandOverScreen>>,
+) {
+    // A replay moves on when the recorded player did
+    let dismissed = match playback {
+        Some(playback) => playback.hand_over_due(),
+        None => keyboard_input.just_pressed(KeyCode::Space),
+    };
+    if !hand_over.waiting || !dismissed {
+        return;
+    }
+
+    if let Some(mut recorder) = recorder {
+        recorder.record_hand_over();
+    }
+    hand_over.waiting = false;
+    timer.resume();
+    for entity in screen_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
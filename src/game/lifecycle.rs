@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+use bevy::state::state::TransitionSchedules;
+use crate::game::game_state::{
+    GameState, GamePhase, TurnTimer, RoundTimer, TransitionTimer, TurnResolution,
+    SettleRules, PlayerIndicator, apply_phase_requests,
+};
+use crate::game::menu::AppState;
+use crate::game::replay::{ReplayPlayback, ReplayRecorder};
+use crate::game::scheme::{MatchSettings, MapSource};
+use crate::game::terrain::{TerrainMap, TerrainRenderer, MapImages, MAP_WIDTH, MAP_HEIGHT};
+use crate::game::worm::{Worm, Gravestone, HealthBar};
+use crate::game::weapons::{Projectile, Explosion, WeaponInventory, WindSystem};
+use crate::game::particles::Particle;
+use crate::game::props::Prop;
+use crate::game::placed::Deployable;
+use crate::game::fire::Fire;
+use crate::game::water::{Water, WaterLevel};
+use crate::game::damage::{DamageNumber, DamageModel};
+use crate::game::ui::GameOverUI;
+use crate::game::aiming::{AimingState, TrajectoryPreview, AimingCrosshair, PowerBar};
+use crate::game::ai::AIController;
+use crate::game::tools::{ToolState, RopeVisual, GirderPreview, GirderVisual};
+use crate::game::strikes::{StrikeTargeting, StrikeMarker};
+use crate::game::hotseat::{HandOver, HandOverScreen};
+use crate::game::stats::MatchStats;
+use crate::game::savegame::{ResumeMatch, SaveRequest};
+
+/// Sets a match up from `MatchSettings` when the game starts and tears it down
+/// when it ends or restarts.
+pub struct MatchLifecyclePlugin;
+
+impl Plugin for MatchLifecyclePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<RestartMatch>()
+            .add_systems(OnEnter(AppState::InGame), start_match)
+            .add_systems(OnExit(AppState::InGame), teardown_match)
+            // Entering the game again from inside it sets the match up afresh, without leaving
+            .add_systems(OnTransition { exited: AppState::InGame, entered: AppState::InGame }, (teardown_match, start_match).chain())
+            // The phase hooks a restart sets off then see the new match, not the old one
+            .configure_sets(StateTransition, TransitionSchedules::<GamePhase>::default()
+                .after(TransitionSchedules::<AppState>::default()))
+            // After the phase requests, so the first turn wins over whatever the old match asked for
+            .add_systems(PostUpdate, restart_match
+                .after(apply_phase_requests)
+                .run_if(in_state(AppState::InGame)));
+    }
+}
+
+/// Where everything random in a match comes from, seeded from `MatchSettings::seed`.
+/// Gameplay systems draw from this instead of `fastrand`'s thread-local generator,
+/// so the match plays out the same whichever threads the systems land on.
+#[derive(Resource, Deref, DerefMut)]
+pub struct MatchRng(pub fastrand::Rng);
+
+/// Starts the match over with the same line-up and scheme.
+#[derive(Event, Clone, Debug)]
+pub struct RestartMatch {
+    /// Roll a new seed rather than play the same map again
+    pub new_map: bool,
+}
+
+type MatchEntityFilter = Or<(
+    With<Worm>,
+    With<Gravestone>,
+    With<Projectile>,
+    With<Explosion>,
+    With<Particle>,
+    With<Prop>,
+    With<Deployable>,
+    With<Fire>,
+    With<Water>,
+    With<TerrainRenderer>,
+)>;
+
+type OverlayFilter = Or<(
+    With<HealthBar>,
+    With<PlayerIndicator>,
+    With<DamageNumber>,
+    With<GameOverUI>,
+    With<TrajectoryPreview>,
+    With<AimingCrosshair>,
+    With<PowerBar>,
+    With<RopeVisual>,
+    With<GirderPreview>,
+    With<GirderVisual>,
+    With<StrikeMarker>,
+    With<HandOverScreen>,
+)>;
+
+/// Resets every piece of match state and spawns the map, worms and props,
+/// or puts back a saved match if one is being resumed.
+pub fn start_match(
+    mut commands: Commands,
+    settings: Res<MatchSettings>,
+    map_images: Res<MapImages>,
+    images: Res<Assets<Image>>,
+    resume: Option<Res<ResumeMatch>>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    info!("Starting a match with the {} scheme, seed {}", settings.scheme.name, settings.seed);
+
+    // Everything random in the match comes from this seed
+    let mut rng = fastrand::Rng::with_seed(settings.seed);
+
+    let saved_terrain = resume.as_ref().and_then(|resume| {
+        let terrain = resume.0.terrain();
+        if terrain.is_none() {
+            warn!("Using the match's map instead of the saved terrain");
+        }
+        terrain
+    });
+
+    let terrain = saved_terrain.or_else(|| match &settings.map {
+        MapSource::Generated => None,
+        MapSource::Image(path) => {
+            let image = map_images.0.get(path).and_then(|handle| images.get(handle));
+            let terrain = image.and_then(TerrainMap::from_image);
+            if terrain.is_none() {
+                warn!("Map {} isn't loaded, generating one instead", path);
+            }
+            terrain
+        }
+    });
+
+    let scheme = settings.scheme.clone();
+    commands.insert_resource(terrain.unwrap_or_else(|| TerrainMap::new(MAP_WIDTH, MAP_HEIGHT, &mut rng)));
+    commands.insert_resource(GameState::new(&settings.teams));
+    commands.insert_resource(TurnTimer::new(scheme.turn_time, scheme.retreat_time));
+    commands.insert_resource(RoundTimer::new(scheme.round_time));
+    commands.insert_resource(TransitionTimer::default());
+    commands.insert_resource(TurnResolution::default());
+    commands.insert_resource(SettleRules::default());
+    commands.insert_resource(WindSystem::new());
+    commands.insert_resource(WaterLevel::default());
+    commands.insert_resource(AimingState::default());
+    commands.insert_resource(AIController::new(settings.ai_difficulty, settings.seed));
+    commands.insert_resource(WeaponInventory::default());
+    commands.insert_resource(ToolState::default());
+    commands.insert_resource(StrikeTargeting::default());
+    commands.insert_resource(HandOver::default());
+    commands.insert_resource(MatchStats::new(settings.teams.len()));
+    commands.insert_resource(SaveRequest::default());
+    commands.insert_resource(DamageModel { falloff: scheme.explosion_falloff, ..default() });
+    commands.insert_resource(scheme);
+    commands.insert_resource(MatchRng(rng));
+
+    // A resumed match doesn't start from the seed, so there is nothing to replay it from
+    if resume.is_none() && playback.is_none() {
+        commands.insert_resource(ReplayRecorder::new(settings.clone()));
+    } else {
+        commands.remove_resource::<ReplayRecorder>();
+    }
+
+    // Runs after the resources above are in place
+    commands.run_system_cached(crate::game::terrain::setup_terrain);
+    if resume.is_some() {
+        commands.run_system_cached(crate::game::savegame::restore_match);
+    } else {
+        commands.run_system_cached(crate::game::worm::spawn_worms);
+        commands.run_system_cached(crate::game::props::spawn_props);
+    }
+}
+
+pub fn teardown_match(
+    mut commands: Commands,
+    match_entities: Query<Entity, MatchEntityFilter>,
+    overlays: Query<Entity, OverlayFilter>,
+) {
+    for entity in match_entities.iter().chain(overlays.iter()) {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn restart_match(
+    mut restart_events: EventReader<RestartMatch>,
+    mut settings: ResMut<MatchSettings>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+) {
+    let Some(restart) = restart_events.read().last() else {
+        return;
+    };
+
+    info!("Restarting match with the {} scheme", settings.scheme.name);
+
+    if restart.new_map {
+        settings.reroll();
+    }
+
+    // The match is torn down and set up again on the way, and starts from the first turn
+    next_app_state.set(AppState::InGame);
+    next_phase.set(GamePhase::PlayerTurn);
+}
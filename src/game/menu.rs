@@ -0,0 +1,513 @@
+use bevy::prelude::*;
+use crate::game::game_state::GameState;
+use crate::game::scheme::{
+    GameScheme, MatchSettings, MatchSetup, MapSource, TeamSetup, TeamController, AIDifficulty,
+    MIN_TEAMS, MAX_TEAMS, MAX_WORMS_PER_TEAM, TEAM_COLORS,
+};
+use crate::game::profiles::{TeamProfiles, GravestoneStyle};
+use crate::game::stats::{StatsBook, MatchStats, TeamRecord};
+use crate::game::terrain::MAP_IMAGES;
+use crate::game::savegame::{SavedMatch, ResumeMatch};
+use crate::game::replay::{Replay, ReplayPlayback};
+
+/// The front end wrapped around a match: menu, setup, the match itself, results.
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_state::<AppState>()
+            .add_systems(OnEnter(AppState::MainMenu), spawn_main_menu)
+            .add_systems(OnEnter(AppState::MatchSetup), spawn_match_setup)
+            .add_systems(OnEnter(AppState::Results), spawn_results)
+            .add_systems(OnEnter(AppState::Stats), spawn_stats)
+            .add_systems(Update, (
+                handle_menu_buttons,
+                highlight_menu_buttons,
+                refresh_match_setup
+                    .run_if(in_state(AppState::MatchSetup).and(resource_changed::<MatchSetup>)),
+            ).chain());
+    }
+}
+
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[states(scoped_entities)]
+pub enum AppState {
+    #[default]
+    MainMenu,
+    MatchSetup,
+    InGame,
+    Results,
+    Stats,
+}
+
+/// What a menu button does when clicked.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub enum MenuButton {
+    Continue,
+    WatchReplay,
+    QuickMatch,
+    OpenSetup,
+    OpenStats,
+    RemoveTeam,
+    AddTeam,
+    CycleProfile(usize),
+    CycleController(usize),
+    CycleColor(usize),
+    CycleGravestone(usize),
+    CycleAlliance(usize),
+    CycleDifficulty,
+    FewerWorms,
+    MoreWorms,
+    CycleMap,
+    NewSeed,
+    CycleScheme,
+    StartMatch,
+    Rematch,
+    MainMenu,
+}
+
+/// Root of the setup screen, rebuilt whenever the settings change.
+#[derive(Component)]
+pub struct MatchSetupScreen;
+
+const BUTTON_COLOR: Color = Color::srgb(0.2, 0.2, 0.25);
+const BUTTON_HOVER_COLOR: Color = Color::srgb(0.3, 0.3, 0.4);
+const BUTTON_PRESSED_COLOR: Color = Color::srgb(0.2, 0.5, 0.2);
+
+fn screen_root(state: AppState) -> impl Bundle {
+    (
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(12.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.05, 0.08, 0.12)),
+        StateScoped(state),
+    )
+}
+
+fn spawn_title(parent: &mut ChildSpawnerCommands, text: &str) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: 48.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 0.0)),
+    ));
+}
+
+fn spawn_button(parent: &mut ChildSpawnerCommands, label: &str, action: MenuButton) {
+    spawn_sized_button(parent, label, action, 220.0);
+}
+
+fn spawn_sized_button(parent: &mut ChildSpawnerCommands, label: &str, action: MenuButton, min_width: f32) {
+    parent.spawn((
+        Button,
+        Node {
+            min_width: Val::Px(min_width),
+            padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(BUTTON_COLOR),
+        action,
+    )).with_children(|button| {
+        button.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+    });
+}
+
+/// A label with `-`/`+` style buttons either side of it.
+fn spawn_stepper(parent: &mut ChildSpawnerCommands, label: String, less: MenuButton, more: MenuButton) {
+    parent.spawn(Node {
+        align_items: AlignItems::Center,
+        column_gap: Val::Px(12.0),
+        ..default()
+    }).with_children(|row| {
+        spawn_small_button(row, "-", less);
+        row.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Node {
+                min_width: Val::Px(200.0),
+                ..default()
+            },
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+        spawn_small_button(row, "+", more);
+    });
+}
+
+fn spawn_small_button(parent: &mut ChildSpawnerCommands, label: &str, action: MenuButton) {
+    parent.spawn((
+        Button,
+        Node {
+            width: Val::Px(36.0),
+            height: Val::Px(36.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(BUTTON_COLOR),
+        action,
+    )).with_children(|button| {
+        button.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+    });
+}
+
+fn spawn_color_button(parent: &mut ChildSpawnerCommands, color: Color, action: MenuButton) {
+    parent.spawn((
+        Button,
+        Node {
+            width: Val::Px(36.0),
+            height: Val::Px(36.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(BUTTON_COLOR),
+        action,
+    )).with_children(|button| {
+        button.spawn((
+            Node {
+                width: Val::Px(24.0),
+                height: Val::Px(24.0),
+                ..default()
+            },
+            BackgroundColor(color),
+        ));
+    });
+}
+
+fn spawn_main_menu(mut commands: Commands) {
+    commands.spawn(screen_root(AppState::MainMenu)).with_children(|parent| {
+        spawn_title(parent, "WASM Worms");
+        if SavedMatch::exists() {
+            spawn_button(parent, "Continue", MenuButton::Continue);
+        }
+        spawn_button(parent, "Quick Match", MenuButton::QuickMatch);
+        spawn_button(parent, "Match Setup", MenuButton::OpenSetup);
+        spawn_button(parent, "Stats", MenuButton::OpenStats);
+        if Replay::exists() {
+            spawn_button(parent, "Watch Replay", MenuButton::WatchReplay);
+        }
+    });
+}
+
+fn spawn_match_setup(mut commands: Commands, settings: Res<MatchSetup>) {
+    commands.spawn((screen_root(AppState::MatchSetup), MatchSetupScreen)).with_children(|parent| {
+        spawn_title(parent, "Match Setup");
+
+        spawn_stepper(
+            parent,
+            format!("Teams: {}", settings.teams.len()),
+            MenuButton::RemoveTeam,
+            MenuButton::AddTeam,
+        );
+        for (index, team) in settings.teams.iter().enumerate() {
+            parent.spawn(Node {
+                column_gap: Val::Px(12.0),
+                ..default()
+            }).with_children(|row| {
+                spawn_sized_button(row, &team.name, MenuButton::CycleProfile(index), 140.0);
+                spawn_sized_button(row, &format!("{:?}", team.controller), MenuButton::CycleController(index), 100.0);
+                spawn_color_button(row, team.color(), MenuButton::CycleColor(index));
+                spawn_sized_button(row, &format!("{:?}", team.gravestone), MenuButton::CycleGravestone(index), 120.0);
+                spawn_sized_button(row, &format!("Alliance {}", alliance_label(team.alliance)), MenuButton::CycleAlliance(index), 120.0);
+            });
+        }
+
+        spawn_button(parent, &format!("AI: {:?}", settings.ai_difficulty), MenuButton::CycleDifficulty);
+        spawn_stepper(
+            parent,
+            format!("Worms per team: {}", settings.worms_per_team),
+            MenuButton::FewerWorms,
+            MenuButton::MoreWorms,
+        );
+
+        let map = match &settings.map {
+            MapSource::Generated => "Generated".to_string(),
+            MapSource::Image(path) => path.clone(),
+        };
+        spawn_button(parent, &format!("Map: {}", map), MenuButton::CycleMap);
+        spawn_button(parent, &format!("Seed: {}", settings.seed), MenuButton::NewSeed);
+        spawn_button(parent, &format!("Scheme: {}", settings.scheme.name), MenuButton::CycleScheme);
+
+        spawn_button(parent, "Start", MenuButton::StartMatch);
+        spawn_button(parent, "Back", MenuButton::MainMenu);
+    });
+}
+
+fn alliance_label(alliance: u32) -> char {
+    char::from_u32('A' as u32 + alliance).unwrap_or('?')
+}
+
+fn refresh_match_setup(
+    mut commands: Commands,
+    settings: Res<MatchSetup>,
+    screen_query: Query<Entity, With<MatchSetupScreen>>,
+) {
+    for entity in screen_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    spawn_match_setup(commands, settings);
+}
+
+fn spawn_line(parent: &mut ChildSpawnerCommands, text: String, color: Color) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(color),
+    ));
+}
+
+fn match_summary(record: &TeamRecord) -> String {
+    format!(
+        "{:.0} damage, {} kills, {} worms lost",
+        record.damage_dealt,
+        record.kills,
+        record.worms_lost,
+    )
+}
+
+fn record_summary(record: &TeamRecord) -> String {
+    format!(
+        "{:.0} damage, {} kills, {:.0} self-damage, {:.0}% accuracy, longest shot {:.0}",
+        record.damage_dealt,
+        record.kills,
+        record.self_damage,
+        record.accuracy() * 100.0,
+        record.longest_shot,
+    )
+}
+
+fn spawn_results(mut commands: Commands, game_state: Res<GameState>, match_stats: Res<MatchStats>) {
+    let outcome = game_state.outcome_text();
+
+    commands.spawn(screen_root(AppState::Results)).with_children(|parent| {
+        spawn_title(parent, &outcome);
+        for (team, record) in game_state.teams.iter().zip(match_stats.teams.iter()) {
+            spawn_line(parent, format!("{}: {}", team.name, match_summary(record)), team.color);
+        }
+
+        let awards = match_stats.awards();
+        if !awards.is_empty() {
+            spawn_line(parent, "Awards".to_string(), Color::srgb(1.0, 1.0, 0.0));
+        }
+        for (award, team, reason) in awards {
+            spawn_line(parent, format!("{}: {}, {}", award, game_state.team_name(team), reason), Color::WHITE);
+        }
+        spawn_button(parent, "Rematch", MenuButton::Rematch);
+        if Replay::exists() {
+            spawn_button(parent, "Watch Replay", MenuButton::WatchReplay);
+        }
+        spawn_button(parent, "Match Setup", MenuButton::OpenSetup);
+        spawn_button(parent, "Main Menu", MenuButton::MainMenu);
+    });
+}
+
+fn spawn_stats(mut commands: Commands, book: Res<StatsBook>) {
+    commands.spawn(screen_root(AppState::Stats)).with_children(|parent| {
+        spawn_title(parent, "Stats");
+
+        if book.teams.is_empty() {
+            spawn_line(parent, "No matches played yet".to_string(), Color::WHITE);
+        }
+        for (name, record) in book.teams.iter() {
+            spawn_line(
+                parent,
+                format!("{}: {} wins from {} matches, {}", name, record.wins, record.matches, record_summary(record)),
+                Color::WHITE,
+            );
+
+            let weapons: Vec<String> = record.weapons.iter()
+                .map(|(weapon, stats)| format!("{} {}/{} ({:.0}%)", weapon, stats.hits, stats.shots, stats.accuracy() * 100.0))
+                .collect();
+            if !weapons.is_empty() {
+                spawn_line(parent, weapons.join(", "), Color::srgb(0.7, 0.7, 0.7));
+            }
+        }
+
+        spawn_button(parent, "Back", MenuButton::MainMenu);
+    });
+}
+
+fn handle_menu_buttons(
+    mut commands: Commands,
+    mut settings: ResMut<MatchSettings>,
+    mut setup: ResMut<MatchSetup>,
+    mut profiles: ResMut<TeamProfiles>,
+    mut next_state: ResMut<NextState<AppState>>,
+    button_query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in button_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *button {
+            MenuButton::Continue => {
+                if let Some(saved) = SavedMatch::load() {
+                    *settings = saved.settings.clone();
+                    commands.insert_resource(ResumeMatch(saved));
+                    next_state.set(AppState::InGame);
+                }
+            }
+            MenuButton::WatchReplay => {
+                if let Some(replay) = Replay::load() {
+                    *settings = replay.settings.clone();
+                    commands.insert_resource(ReplayPlayback::new(replay));
+                    next_state.set(AppState::InGame);
+                }
+            }
+            MenuButton::QuickMatch => {
+                // The setup as it stands, on a map nobody has played yet
+                *settings = setup.0.clone();
+                settings.reroll();
+                next_state.set(AppState::InGame);
+            }
+            MenuButton::StartMatch => {
+                *settings = setup.0.clone();
+                next_state.set(AppState::InGame);
+            }
+            MenuButton::OpenSetup => next_state.set(AppState::MatchSetup),
+            MenuButton::OpenStats => next_state.set(AppState::Stats),
+            MenuButton::MainMenu => next_state.set(AppState::MainMenu),
+            // Same settings, seed and all
+            MenuButton::Rematch => next_state.set(AppState::InGame),
+            MenuButton::RemoveTeam => {
+                if setup.teams.len() > MIN_TEAMS {
+                    setup.teams.pop();
+                    let last_alliance = setup.teams.len() as u32 - 1;
+                    for team in setup.teams.iter_mut() {
+                        team.alliance = team.alliance.min(last_alliance);
+                    }
+                }
+            }
+            MenuButton::AddTeam => {
+                if setup.teams.len() < MAX_TEAMS {
+                    let index = setup.teams.len();
+                    setup.teams.push(TeamSetup::for_slot(index, &profiles, TeamController::AI));
+                }
+            }
+            MenuButton::CycleProfile(index) => {
+                if let Some(team) = setup.teams.get_mut(index) {
+                    let current = profiles.profiles.iter()
+                        .position(|profile| profile.name == team.name);
+                    let next = current.map_or(0, |current| (current + 1) % profiles.profiles.len());
+                    if let Some(profile) = profiles.profiles.get(next) {
+                        team.apply_profile(profile);
+                    }
+                }
+            }
+            MenuButton::CycleColor(index) => {
+                if let Some(team) = setup.teams.get_mut(index) {
+                    let current = TEAM_COLORS.iter().position(|color| *color == team.color);
+                    team.color = TEAM_COLORS[current.map_or(0, |current| (current + 1) % TEAM_COLORS.len())];
+                    // Remembered for next time
+                    if let Some(profile) = profiles.find_mut(&team.name) {
+                        profile.color = team.color;
+                        profiles.save();
+                    }
+                }
+            }
+            MenuButton::CycleGravestone(index) => {
+                if let Some(team) = setup.teams.get_mut(index) {
+                    let current = GravestoneStyle::ALL.iter().position(|style| *style == team.gravestone);
+                    team.gravestone = GravestoneStyle::ALL[current.map_or(0, |current| (current + 1) % GravestoneStyle::ALL.len())];
+                    if let Some(profile) = profiles.find_mut(&team.name) {
+                        profile.gravestone = team.gravestone;
+                        profiles.save();
+                    }
+                }
+            }
+            MenuButton::CycleController(index) => {
+                if let Some(team) = setup.teams.get_mut(index) {
+                    // Remote isn't offered until something drives it over the network
+                    team.controller = match team.controller {
+                        TeamController::Human => TeamController::AI,
+                        TeamController::AI | TeamController::Remote => TeamController::Human,
+                    };
+                }
+            }
+            MenuButton::CycleAlliance(index) => {
+                let team_count = setup.teams.len() as u32;
+                if let Some(team) = setup.teams.get_mut(index) {
+                    team.alliance = (team.alliance + 1) % team_count;
+                }
+            }
+            MenuButton::CycleDifficulty => {
+                let current = AIDifficulty::ALL.iter()
+                    .position(|difficulty| *difficulty == setup.ai_difficulty)
+                    .unwrap_or(0);
+                setup.ai_difficulty = AIDifficulty::ALL[(current + 1) % AIDifficulty::ALL.len()];
+            }
+            MenuButton::FewerWorms => {
+                setup.worms_per_team = (setup.worms_per_team - 1).max(1);
+            }
+            MenuButton::MoreWorms => {
+                setup.worms_per_team = (setup.worms_per_team + 1).min(MAX_WORMS_PER_TEAM);
+            }
+            MenuButton::CycleMap => {
+                // Generated first, then each of the map images
+                setup.map = match &setup.map {
+                    MapSource::Generated => MAP_IMAGES.first()
+                        .map(|path| MapSource::Image(path.to_string()))
+                        .unwrap_or(MapSource::Generated),
+                    MapSource::Image(current) => MAP_IMAGES.iter()
+                        .position(|path| path == current)
+                        .and_then(|index| MAP_IMAGES.get(index + 1))
+                        .map(|path| MapSource::Image(path.to_string()))
+                        .unwrap_or(MapSource::Generated),
+                };
+            }
+            MenuButton::NewSeed => setup.reroll(),
+            MenuButton::CycleScheme => {
+                let presets = GameScheme::presets();
+                let current = presets.iter()
+                    .position(|scheme| scheme.name == setup.scheme.name)
+                    .unwrap_or(0);
+                setup.scheme = presets[(current + 1) % presets.len()].clone();
+            }
+        }
+    }
+}
+
+fn highlight_menu_buttons(
+    mut button_query: Query<(&Interaction, &mut BackgroundColor), (With<MenuButton>, Changed<Interaction>)>,
+) {
+    for (interaction, mut color) in button_query.iter_mut() {
+        color.0 = match interaction {
+            Interaction::Pressed => BUTTON_PRESSED_COLOR,
+            Interaction::Hovered => BUTTON_HOVER_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        };
+    }
+}
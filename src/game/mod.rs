@@ -2,6 +2,7 @@ use bevy::prelude::*;
 
 pub mod physics;
 pub mod terrain;
+pub mod terrain_codec;
 pub mod worm;
 pub mod game_state;
 pub mod camera;
@@ -10,6 +11,24 @@ pub mod aiming;
 pub mod ui;
 pub mod particles;
 pub mod ai;
+pub mod strikes;
+pub mod placed;
+pub mod tools;
+pub mod fire;
+pub mod props;
+pub mod damage;
+pub mod events;
+pub mod scheme;
+pub mod water;
+pub mod lifecycle;
+pub mod menu;
+pub mod hotseat;
+pub mod storage;
+pub mod profiles;
+pub mod stats;
+pub mod savegame;
+pub mod replay;
+pub mod player_input;
 
 use physics::PhysicsPlugin;
 use terrain::TerrainPlugin;
@@ -21,6 +40,23 @@ use aiming::AimingPlugin;
 use ui::UIPlugin;
 use particles::ParticlePlugin;
 use ai::AIPlugin;
+use strikes::StrikePlugin;
+use placed::PlacedWeaponPlugin;
+use tools::ToolPlugin;
+use fire::FirePlugin;
+use props::PropPlugin;
+use damage::DamagePlugin;
+use events::GameEventsPlugin;
+use scheme::SchemePlugin;
+use water::WaterPlugin;
+use lifecycle::MatchLifecyclePlugin;
+use menu::MenuPlugin;
+use hotseat::HotSeatPlugin;
+use profiles::ProfilePlugin;
+use stats::StatsPlugin;
+use savegame::SaveGamePlugin;
+use replay::ReplayPlugin;
+use player_input::PlayerInputPlugin;
 
 pub struct GamePlugin;
 
@@ -28,6 +64,9 @@ impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app
             .add_plugins((
+                ProfilePlugin,
+                SchemePlugin,
+                MenuPlugin,
                 PhysicsPlugin,
                 TerrainPlugin,
                 WormPlugin,
@@ -38,6 +77,22 @@ impl Plugin for GamePlugin {
                 UIPlugin,
                 ParticlePlugin,
                 AIPlugin,
+                PlayerInputPlugin,
+            ))
+            .add_plugins((
+                StrikePlugin,
+                PlacedWeaponPlugin,
+                ToolPlugin,
+                FirePlugin,
+                PropPlugin,
+                DamagePlugin,
+                GameEventsPlugin,
+                WaterPlugin,
+                MatchLifecyclePlugin,
+                HotSeatPlugin,
+                StatsPlugin,
+                SaveGamePlugin,
+                ReplayPlugin,
             ))
             .add_systems(Startup, setup_camera);
     }
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use crate::game::replay::simulation_running;
 
 pub struct ParticlePlugin;
 
@@ -7,7 +8,7 @@ impl Plugin for ParticlePlugin {
         app.add_systems(Update, (
             update_particles,
             cleanup_expired_particles,
-        ));
+        ).run_if(simulation_running));
     }
 }
 
@@ -15,7 +16,6 @@ impl Plugin for ParticlePlugin {
 pub struct Particle {
     pub velocity: Vec2,
     pub lifetime: Timer,
-    pub fade_rate: f32,
     pub gravity_scale: f32,
 }
 
@@ -27,7 +27,6 @@ impl Particle {
         Self {
             velocity,
             lifetime: Timer::from_seconds(lifetime, TimerMode::Once),
-            fade_rate: 1.0 / lifetime,
             gravity_scale,
         }
     }
@@ -37,19 +36,20 @@ pub fn spawn_explosion_particles(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<ColorMaterial>>,
+    rng: &mut fastrand::Rng,
     position: Vec3,
     count: usize,
 ) {
     for _ in 0..count {
-        let angle = fastrand::f32() * std::f32::consts::TAU;
-        let speed = fastrand::f32() * 300.0 + 100.0;
+        let angle = rng.f32() * std::f32::consts::TAU;
+        let speed = rng.f32() * 300.0 + 100.0;
         let velocity = Vec2::new(angle.cos() * speed, angle.sin() * speed);
         
-        let lifetime = fastrand::f32() * 1.5 + 0.5;
-        let size = fastrand::f32() * 4.0 + 2.0;
+        let lifetime = rng.f32() * 1.5 + 0.5;
+        let size = rng.f32() * 4.0 + 2.0;
         
         // Random explosion colors
-        let color = match fastrand::u32(0..4) {
+        let color = match rng.u32(0..4) {
             0 => Color::srgb(1.0, 0.5, 0.0), // Orange
             1 => Color::srgb(1.0, 0.8, 0.0), // Yellow
             2 => Color::srgb(0.8, 0.2, 0.0), // Red
@@ -60,8 +60,8 @@ pub fn spawn_explosion_particles(
             Mesh2d(meshes.add(bevy::math::primitives::Circle::new(size))),
             MeshMaterial2d(materials.add(ColorMaterial::from(color))),
             Transform::from_translation(position + Vec3::new(
-                (fastrand::f32() - 0.5) * 20.0,
-                (fastrand::f32() - 0.5) * 20.0,
+                (rng.f32() - 0.5) * 20.0,
+                (rng.f32() - 0.5) * 20.0,
                 0.1,
             )),
             Particle::new(velocity, lifetime, 0.8),
@@ -74,23 +74,24 @@ pub fn spawn_dirt_particles(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<ColorMaterial>>,
+    rng: &mut fastrand::Rng,
     position: Vec3,
     count: usize,
 ) {
     for _ in 0..count {
-        let angle = fastrand::f32() * std::f32::consts::PI; // Only upward
-        let speed = fastrand::f32() * 200.0 + 50.0;
+        let angle = rng.f32() * std::f32::consts::PI; // Only upward
+        let speed = rng.f32() * 200.0 + 50.0;
         let velocity = Vec2::new(angle.cos() * speed, angle.sin() * speed);
         
-        let lifetime = fastrand::f32() * 2.0 + 1.0;
-        let size = fastrand::f32() * 3.0 + 1.0;
+        let lifetime = rng.f32() * 2.0 + 1.0;
+        let size = rng.f32() * 3.0 + 1.0;
         
         commands.spawn((
             Mesh2d(meshes.add(bevy::math::primitives::Circle::new(size))),
             MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(0.4, 0.3, 0.2)))),
             Transform::from_translation(position + Vec3::new(
-                (fastrand::f32() - 0.5) * 30.0,
-                (fastrand::f32() - 0.5) * 10.0,
+                (rng.f32() - 0.5) * 30.0,
+                (rng.f32() - 0.5) * 10.0,
                 0.05,
             )),
             Particle::new(velocity, lifetime, 1.2),
@@ -101,10 +102,10 @@ pub fn spawn_dirt_particles(
 
 fn update_particles(
     time: Res<Time>,
-    mut particle_query: Query<(&mut Transform, &mut Particle, &mut MeshMaterial2d<ColorMaterial>)>,
+    mut particle_query: Query<(&mut Transform, &mut Particle, &MeshMaterial2d<ColorMaterial>)>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    for (mut transform, mut particle, mut material_handle) in particle_query.iter_mut() {
+    for (mut transform, mut particle, material_handle) in particle_query.iter_mut() {
         // Update lifetime
         particle.lifetime.tick(time.delta());
         
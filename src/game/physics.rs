@@ -1,4 +1,6 @@
 use bevy::prelude::*;
+use crate::game::replay::simulation_running;
+use crate::game::terrain::TerrainMap;
 
 pub struct PhysicsPlugin;
 
@@ -9,14 +11,14 @@ impl Plugin for PhysicsPlugin {
                 apply_gravity,
                 apply_velocity,
                 check_ground_collision,
-            ).chain());
+                terrain_body_collision,
+            ).chain().run_if(simulation_running));
     }
 }
 
 #[derive(Component)]
 pub struct RigidBody {
     pub velocity: Vec2,
-    pub mass: f32,
     pub bounce: f32,
     pub friction: f32,
     pub gravity_scale: f32,
@@ -26,7 +28,6 @@ impl Default for RigidBody {
     fn default() -> Self {
         Self {
             velocity: Vec2::ZERO,
-            mass: 1.0,
             bounce: 0.3,
             friction: 0.8,
             gravity_scale: 1.0,
@@ -49,8 +50,12 @@ impl Default for Collider {
     }
 }
 
+/// Marks bodies that rest on the destructible terrain instead of exploding on contact.
+#[derive(Component)]
+pub struct TerrainBody;
+
 const GRAVITY: f32 = -980.0; // pixels per second squared
-const GROUND_Y: f32 = -300.0; // temporary ground level
+pub const GROUND_Y: f32 = -300.0; // temporary ground level
 
 fn apply_gravity(
     time: Res<Time>,
@@ -97,4 +102,46 @@ fn check_ground_collision(
             collider.is_grounded = false;
         }
     }
-}
\ No newline at end of file
+}
+
+fn terrain_body_collision(
+    terrain: Res<TerrainMap>,
+    mut query: Query<(&mut Transform, &mut RigidBody, &mut Collider), With<TerrainBody>>,
+) {
+    for (mut transform, mut body, mut collider) in query.iter_mut() {
+        let world_x = transform.translation.x + (terrain.width as f32 / 2.0);
+        let world_y = transform.translation.y + (terrain.height as f32 / 2.0);
+        
+        // Check collision with terrain
+        if terrain.check_collision(world_x, world_y, collider.radius) {
+            // Simple collision response - push out of terrain
+            let mut found_safe_position = false;
+            
+            // Try moving up to find safe position
+            for offset in 1..=32 {
+                let test_y = world_y + offset as f32;
+                if !terrain.check_collision(world_x, test_y, collider.radius) {
+                    transform.translation.y = test_y - (terrain.height as f32 / 2.0);
+                    body.velocity.y = body.velocity.y.max(0.0); // Stop downward movement
+                    collider.is_grounded = true;
+                    found_safe_position = true;
+                    break;
+                }
+            }
+            
+            if !found_safe_position {
+                // If we can't find a safe position above, try moving horizontally
+                for x_offset in [-1, 1] {
+                    for offset in 1..=32 {
+                        let test_x = world_x + (x_offset * offset) as f32;
+                        if !terrain.check_collision(test_x, world_y, collider.radius) {
+                            transform.translation.x = test_x - (terrain.width as f32 / 2.0);
+                            body.velocity.x = 0.0;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use bevy::prelude::*;
+use crate::game::physics::{RigidBody, Collider, TerrainBody};
+use crate::game::weapons::{WeaponInventory, WeaponCategory, WeaponType, detonate};
+use crate::game::worm::{Worm, ActiveWorm};
+use crate::game::replay::simulation_running;
+use crate::game::game_state::{GameState, GamePhase, TurnFlow};
+use crate::game::terrain::TerrainMap;
+use crate::game::lifecycle::MatchRng;
+use crate::game::events::{DamageCause, ProjectileFired, IssueWormCommands, WormCommand};
+
+pub struct PlacedWeaponPlugin;
+
+impl Plugin for PlacedWeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, (
+                handle_placement.after(IssueWormCommands),
+                sheep_movement,
+                sheep_detonation.after(IssueWormCommands),
+                proximity_trigger_system,
+                fuse_system,
+            ).run_if(simulation_running));
+    }
+}
+
+/// A weapon that has been put down in the world and waits to go off.
+#[derive(Component)]
+pub struct Deployable {
+    pub weapon_type: WeaponType,
+    pub owner_team: Option<u32>,
+    pub damage: f32,
+    pub explosion_radius: f32,
+}
+
+/// A lit fuse; the deployable explodes when the timer finishes.
+#[derive(Component)]
+pub struct Fuse {
+    pub timer: Timer,
+}
+
+impl Fuse {
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+        }
+    }
+}
+
+/// Lights a fuse once a living worm comes within `radius` after the arming delay.
+/// Entities with this component stay on the map across turns until triggered.
+#[derive(Component)]
+pub struct ProximityTrigger {
+    pub radius: f32,
+    pub arm_timer: Timer,
+    pub fuse_time: f32,
+    pub triggered: bool,
+    /// The worm that put the mine down, left out until it has walked clear
+    pub placed_by: Option<Entity>,
+}
+
+#[derive(Component)]
+pub struct Sheep {
+    pub direction: f32,
+    pub walk_speed: f32,
+    pub hop_force: f32,
+    pub hops_without_progress: u32,
+    pub last_x: f32,
+}
+
+fn handle_placement(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut worm_commands: EventReader<WormCommand>,
+    game_state: Res<GameState>,
+    mut turn: TurnFlow,
+    weapon_inventory: Res<WeaponInventory>,
+    worm_query: Query<(Entity, &Transform, &Worm, &Collider), With<ActiveWorm>>,
+) {
+    let place = worm_commands.read()
+        .filter(|command| **command == WormCommand::Place)
+        .count() > 0;
+    if !turn.can_player_act() || !place {
+        return;
+    }
+
+    let current_weapon = match weapon_inventory.weapons.get(weapon_inventory.current_weapon) {
+        Some(weapon) if weapon.category() == WeaponCategory::Placed => weapon.clone(),
+        _ => return,
+    };
+
+    let Some((worm_entity, worm_transform, worm, collider)) = worm_query.iter()
+        .find(|(_, _, worm, _)| worm.team == game_state.current_player && worm.health > 0.0) else {
+        return;
+    };
+
+    // Drop it just in front of the worm's feet
+    let position = worm_transform.translation + Vec3::new(
+        worm.facing * (collider.radius + 8.0),
+        0.0,
+        0.1,
+    );
+
+    let placed = place_weapon(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        current_weapon.clone(),
+        Some(game_state.current_player),
+        position,
+        worm.facing,
+    );
+
+    // Don't blow up the worm that is still standing on its own mine
+    if current_weapon == WeaponType::Mine {
+        commands.entity(placed).entry::<ProximityTrigger>().and_modify(move |mut trigger| {
+            trigger.placed_by = Some(worm_entity);
+        });
+    }
+
+    // Resolves like a shot in flight; the turn moves on once everything has settled
+    turn.projectile_launched();
+}
+
+/// Spawns a placed weapon at `position`. `facing` is the direction a sheep sets off in.
+/// Map hazards spawned at game start have no `owner_team`.
+pub fn place_weapon(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    weapon_type: WeaponType,
+    owner_team: Option<u32>,
+    position: Vec3,
+    facing: f32,
+) -> Entity {
+    let stats = weapon_type.get_stats();
+    let fuse_time = stats.fuse_time.unwrap_or(3.0);
+
+    let mut entity = commands.spawn((
+        Transform::from_translation(position),
+        Deployable {
+            weapon_type: weapon_type.clone(),
+            owner_team,
+            damage: stats.damage,
+            explosion_radius: stats.explosion_radius,
+        },
+        RigidBody {
+            velocity: Vec2::ZERO,
+            gravity_scale: stats.gravity_scale,
+            bounce: 0.1,
+            friction: 0.5,
+        },
+        TerrainBody,
+    ));
+
+    let id = entity.id();
+    match weapon_type {
+        WeaponType::Dynamite => {
+            entity.insert((
+                Mesh2d(meshes.add(bevy::math::primitives::Rectangle::new(6.0, 14.0))),
+                MeshMaterial2d(materials.add(ColorMaterial::from(weapon_type.get_color()))),
+                Collider { radius: 6.0, is_grounded: false },
+                Fuse::new(fuse_time),
+            ));
+        }
+        WeaponType::Mine => {
+            entity.insert((
+                Mesh2d(meshes.add(bevy::math::primitives::Circle::new(6.0))),
+                MeshMaterial2d(materials.add(ColorMaterial::from(weapon_type.get_color()))),
+                Collider { radius: 6.0, is_grounded: false },
+                ProximityTrigger {
+                    radius: 50.0,
+                    arm_timer: Timer::from_seconds(2.0, TimerMode::Once),
+                    fuse_time,
+                    triggered: false,
+                    placed_by: None,
+                },
+            ));
+        }
+        WeaponType::Sheep => {
+            entity.insert((
+                Mesh2d(meshes.add(bevy::math::primitives::Circle::new(10.0))),
+                MeshMaterial2d(materials.add(ColorMaterial::from(weapon_type.get_color()))),
+                Collider { radius: 10.0, is_grounded: false },
+                Fuse::new(fuse_time),
+                Sheep {
+                    direction: facing.signum(),
+                    walk_speed: 120.0,
+                    hop_force: 300.0,
+                    hops_without_progress: 0,
+                    last_x: position.x,
+                },
+            ));
+        }
+        _ => {}
+    }
+    
+    if owner_team.is_some() {
+        commands.send_event(ProjectileFired {
+            team: owner_team,
+            weapon: weapon_type,
+            position: position.truncate(),
+            velocity: Vec2::ZERO,
+        });
+    }
+
+    id
+}
+
+fn sheep_movement(
+    terrain: Res<TerrainMap>,
+    mut sheep_query: Query<(&Transform, &mut RigidBody, &Collider, &mut Sheep)>,
+) {
+    for (transform, mut body, collider, mut sheep) in sheep_query.iter_mut() {
+        body.velocity.x = sheep.direction * sheep.walk_speed;
+
+        if !collider.is_grounded {
+            continue;
+        }
+
+        // Hop when there is a wall in front
+        let world_x = transform.translation.x + (terrain.width as f32 / 2.0);
+        let world_y = transform.translation.y + (terrain.height as f32 / 2.0);
+        let ahead_x = world_x + sheep.direction * (collider.radius + 2.0);
+
+        if terrain.check_collision(ahead_x, world_y + 4.0, collider.radius * 0.5) {
+            body.velocity.y = sheep.hop_force;
+
+            // Turn around if hopping doesn't get us anywhere
+            if (transform.translation.x - sheep.last_x).abs() < 5.0 {
+                sheep.hops_without_progress += 1;
+            } else {
+                sheep.hops_without_progress = 0;
+            }
+            sheep.last_x = transform.translation.x;
+
+            if sheep.hops_without_progress >= 3 {
+                sheep.direction = -sheep.direction;
+                sheep.hops_without_progress = 0;
+            }
+        }
+    }
+}
+
+fn sheep_detonation(
+    mut worm_commands: EventReader<WormCommand>,
+    game_state: Res<GameState>,
+    phase: Res<State<GamePhase>>,
+    mut sheep_query: Query<(&mut Fuse, &Deployable), With<Sheep>>,
+) {
+    let detonate = worm_commands.read()
+        .filter(|command| **command == WormCommand::Detonate)
+        .count() > 0;
+    if !detonate || *phase.get() != GamePhase::ProjectileFlying {
+        return;
+    }
+
+    // Sets off the team's own walking sheep, leaving any others to their fuses
+    for (mut fuse, deployable) in sheep_query.iter_mut() {
+        if deployable.owner_team == Some(game_state.current_player) {
+            let remaining = fuse.timer.remaining();
+            fuse.timer.tick(remaining);
+        }
+    }
+}
+
+fn proximity_trigger_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut trigger_query: Query<(Entity, &Transform, &mut ProximityTrigger)>,
+    worm_query: Query<(Entity, &Transform, &Worm)>,
+) {
+    for (entity, transform, mut trigger) in trigger_query.iter_mut() {
+        if trigger.triggered {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        let radius = trigger.radius;
+        let in_range = |worm_transform: &Transform| {
+            worm_transform.translation.truncate().distance(position) <= radius
+        };
+
+        // The placer counts like any other worm once it has been out of range
+        if let Some(placer) = trigger.placed_by {
+            let still_close = worm_query.get(placer)
+                .is_ok_and(|(_, worm_transform, _)| in_range(worm_transform));
+            if !still_close {
+                trigger.placed_by = None;
+            }
+        }
+
+        trigger.arm_timer.tick(time.delta());
+        if !trigger.arm_timer.finished() {
+            continue;
+        }
+
+        let worm_nearby = worm_query.iter().any(|(worm_entity, worm_transform, worm)| {
+            worm.health > 0.0
+                && Some(worm_entity) != trigger.placed_by
+                && in_range(worm_transform)
+        });
+
+        if worm_nearby {
+            trigger.triggered = true;
+            commands.entity(entity).insert(Fuse::new(trigger.fuse_time));
+        }
+    }
+}
+
+fn fuse_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<MatchRng>,
+    mut turn: TurnFlow,
+    time: Res<Time>,
+    mut fuse_query: Query<(Entity, &Transform, &mut Fuse, &Deployable)>,
+) {
+    for (entity, transform, mut fuse, deployable) in fuse_query.iter_mut() {
+        fuse.timer.tick(time.delta());
+
+        if fuse.timer.finished() {
+            commands.entity(entity).despawn();
+
+            detonate(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                &mut rng,
+                &mut turn,
+                transform.translation,
+                deployable.explosion_radius,
+                deployable.damage,
+                deployable.owner_team,
+                DamageCause::Weapon(deployable.weapon_type.clone()),
+            );
+        }
+    }
+}
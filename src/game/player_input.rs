@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use crate::game::events::{IssueWormCommands, WormCommand};
+use crate::game::hotseat::local_input_allowed;
+use crate::game::replay::{simulation_running, watching_replay};
+use crate::game::weapons::{WeaponInventory, WeaponCategory, WeaponType};
+use crate::game::game_state::GamePhase;
+use crate::game::camera::WorldCursor;
+
+/// Turns the keyboard and mouse into `WormCommand`s for the local team whose turn it is.
+pub struct PlayerInputPlugin;
+
+impl Plugin for PlayerInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, read_player_input
+            .in_set(IssueWormCommands)
+            .run_if(simulation_running.and(local_input_allowed).and(not(watching_replay))));
+    }
+}
+
+// Degrees per second the aim turns while an arrow key is held
+const AIM_SPEED: f32 = 90.0;
+
+fn read_player_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    phase: Res<State<GamePhase>>,
+    weapon_inventory: Res<WeaponInventory>,
+    world_cursor: WorldCursor,
+    time: Res<Time>,
+    mut worm_commands: EventWriter<WormCommand>,
+) {
+    let current_weapon = weapon_inventory.weapons.get(weapon_inventory.current_weapon);
+
+    let mut direction = 0.0;
+    if keyboard_input.pressed(KeyCode::ArrowLeft) {
+        direction -= 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::ArrowRight) {
+        direction += 1.0;
+    }
+
+    // Sent every frame so the worm stops when the keys are let go. It also swings
+    // the rope, steers the jetpack and picks the way a strike flies.
+    worm_commands.write(WormCommand::Move(direction));
+    if keyboard_input.just_pressed(KeyCode::KeyW) {
+        worm_commands.write(WormCommand::Jump);
+    }
+
+    // Space does whatever suits the phase and the weapon in hand
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        let command = match phase.get() {
+            GamePhase::UsingTool => Some(WormCommand::LetGo),
+            GamePhase::ProjectileFlying => Some(WormCommand::Detonate),
+            _ => match current_weapon {
+                Some(WeaponType::Jetpack) => Some(WormCommand::TakeOff),
+                Some(weapon) if weapon.category() == WeaponCategory::Placed => Some(WormCommand::Place),
+                Some(weapon) if weapon.category() == WeaponCategory::Tool => None,
+                _ => Some(WormCommand::ToggleAim),
+            },
+        };
+        if let Some(command) = command {
+            worm_commands.write(command);
+        }
+    }
+    // W lets go of the rope too, but doesn't switch off the jetpack
+    if keyboard_input.just_pressed(KeyCode::KeyW)
+        && *phase.get() == GamePhase::UsingTool
+        && current_weapon == Some(&WeaponType::NinjaRope)
+    {
+        worm_commands.write(WormCommand::LetGo);
+    }
+
+    // The arrows turn the aim as well, which only counts while aiming
+    if direction != 0.0 {
+        worm_commands.write(WormCommand::AimBy(-direction * AIM_SPEED * time.delta_secs()));
+    }
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        worm_commands.write(WormCommand::BeginCharge);
+    }
+    if keyboard_input.just_released(KeyCode::Enter) {
+        worm_commands.write(WormCommand::Release);
+    }
+
+    // Up and down climb the rope and fire the jetpack, tapping them turns a girder
+    let mut climb = 0.0;
+    if keyboard_input.pressed(KeyCode::ArrowUp) {
+        climb += 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::ArrowDown) {
+        climb -= 1.0;
+    }
+    if climb != 0.0 {
+        worm_commands.write(WormCommand::Climb(climb));
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        worm_commands.write(WormCommand::RotateGirder(45.0));
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        worm_commands.write(WormCommand::RotateGirder(-45.0));
+    }
+
+    if mouse_input.just_pressed(MouseButton::Left) {
+        if let Some(target) = world_cursor.position() {
+            worm_commands.write(WormCommand::Target(target));
+        }
+    }
+
+    let weapon_count = weapon_inventory.weapons.len();
+    let current = weapon_inventory.current_weapon;
+    for (index, key) in [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3].into_iter().enumerate() {
+        if keyboard_input.just_pressed(key) {
+            worm_commands.write(WormCommand::SelectWeapon(index));
+        }
+    }
+    if weapon_count > 0 && keyboard_input.just_pressed(KeyCode::KeyQ) {
+        worm_commands.write(WormCommand::SelectWeapon((current + weapon_count - 1) % weapon_count));
+    }
+    if weapon_count > 0 && keyboard_input.just_pressed(KeyCode::KeyE) {
+        worm_commands.write(WormCommand::SelectWeapon((current + 1) % weapon_count));
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Enter) || keyboard_input.just_pressed(KeyCode::Tab) {
+        worm_commands.write(WormCommand::EndTurn);
+    }
+}
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::game::scheme::{TEAM_COLORS, MAX_TEAMS};
+use crate::game::storage;
+
+/// Saved teams to pick from in match setup. Added before `SchemePlugin` so the
+/// default line-up can use them.
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        let profiles = TeamProfiles::load().unwrap_or_else(|| {
+            // Write the defaults out so there is a file to edit
+            let profiles = TeamProfiles::default();
+            profiles.save();
+            profiles
+        });
+
+        app.insert_resource(profiles);
+    }
+}
+
+const PROFILES_KEY: &str = "profiles";
+
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TeamProfiles {
+    pub profiles: Vec<TeamProfile>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TeamProfile {
+    pub name: String,
+    /// Worms are named in order, wrapping round if the team has more worms than names
+    pub worm_names: Vec<String>,
+    pub color: [f32; 3],
+    pub gravestone: GravestoneStyle,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GravestoneStyle {
+    Headstone,
+    Obelisk,
+    Urn,
+}
+
+impl GravestoneStyle {
+    pub const ALL: [GravestoneStyle; 3] = [GravestoneStyle::Headstone, GravestoneStyle::Obelisk, GravestoneStyle::Urn];
+
+    pub fn mesh(&self) -> Mesh {
+        match self {
+            GravestoneStyle::Headstone => bevy::math::primitives::Rectangle::new(14.0, 20.0).into(),
+            GravestoneStyle::Obelisk => bevy::math::primitives::Triangle2d::new(
+                Vec2::new(0.0, 14.0),
+                Vec2::new(-7.0, -10.0),
+                Vec2::new(7.0, -10.0),
+            ).into(),
+            GravestoneStyle::Urn => bevy::math::primitives::Capsule2d::new(7.0, 8.0).into(),
+        }
+    }
+}
+
+const DEFAULT_PROFILES: [(&str, [&str; 4]); MAX_TEAMS] = [
+    ("Green", ["Boggy", "Spadge", "Clagnut", "Thumper"]),
+    ("Red", ["Chuck", "Nobby", "Bert", "Grub"]),
+    ("Blue", ["Wiggles", "Squirm", "Noodle", "Slinky"]),
+    ("Yellow", ["Sunny", "Custard", "Banana", "Lemon"]),
+    ("Purple", ["Grape", "Plum", "Violet", "Mauve"]),
+    ("Cyan", ["Splash", "Ripple", "Drizzle", "Puddle"]),
+    ("Orange", ["Tango", "Satsuma", "Ginger", "Pumpkin"]),
+    ("White", ["Ghost", "Snowy", "Chalky", "Frost"]),
+];
+
+impl Default for TeamProfiles {
+    fn default() -> Self {
+        Self {
+            profiles: DEFAULT_PROFILES.iter().zip(TEAM_COLORS)
+                .map(|((name, worm_names), color)| TeamProfile {
+                    name: name.to_string(),
+                    worm_names: worm_names.iter().map(|name| name.to_string()).collect(),
+                    color,
+                    gravestone: GravestoneStyle::Headstone,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TeamProfiles {
+    pub fn load() -> Option<Self> {
+        let source = storage::load(PROFILES_KEY)?;
+        match ron::from_str::<TeamProfiles>(&source) {
+            Ok(profiles) if !profiles.profiles.is_empty() => Some(profiles),
+            Ok(_) => None,
+            Err(error) => {
+                warn!("Ignoring saved team profiles, {}", error);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|source| storage::save(PROFILES_KEY, &source).map_err(|error| error.to_string()));
+        if let Err(error) = result {
+            warn!("Couldn't save team profiles, {}", error);
+        }
+    }
+
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut TeamProfile> {
+        self.profiles.iter_mut().find(|profile| profile.name == name)
+    }
+}
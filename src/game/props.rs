@@ -0,0 +1,252 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::game::replay::simulation_running;
+use crate::game::physics::{RigidBody, Collider, TerrainBody};
+use crate::game::placed::place_weapon;
+use crate::game::weapons::{WeaponType, detonate};
+use crate::game::worm::Worm;
+use crate::game::game_state::TurnFlow;
+use crate::game::terrain::TerrainMap;
+use crate::game::events::{ApplyDamage, ApplyHealing, DamageCause};
+use crate::game::lifecycle::MatchRng;
+
+pub struct PropPlugin;
+
+impl Plugin for PropPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, (
+                burn_props,
+                destroy_damaged_props,
+                collect_health_crates,
+            ).run_if(simulation_running));
+    }
+}
+
+/// A destructible object on the map. Props take explosion damage like worms
+/// and go off through the normal explosion pipeline when destroyed.
+#[derive(Component)]
+pub struct Prop {
+    pub kind: PropKind,
+    pub health: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PropKind {
+    OilBarrel,
+    HealthCrate,
+}
+
+impl PropKind {
+    pub fn max_health(&self) -> f32 {
+        match self {
+            PropKind::OilBarrel => 20.0,
+            PropKind::HealthCrate => 10.0,
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        match self {
+            PropKind::OilBarrel => Vec2::new(16.0, 22.0),
+            PropKind::HealthCrate => Vec2::new(18.0, 18.0),
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PropKind::OilBarrel => Color::srgb(0.7, 0.15, 0.1),
+            PropKind::HealthCrate => Color::srgb(0.9, 0.9, 0.9),
+        }
+    }
+
+    /// Explosion radius and damage when the prop is destroyed.
+    pub fn death_explosion(&self) -> (f32, f32) {
+        match self {
+            PropKind::OilBarrel => (75.0, 40.0),
+            PropKind::HealthCrate => (30.0, 10.0),
+        }
+    }
+}
+
+const OIL_BARREL_COUNT: usize = 4;
+const HEALTH_CRATE_COUNT: usize = 2;
+const MINE_COUNT: usize = 3;
+const BARREL_FIRE_COUNT: usize = 10;
+const HEALTH_CRATE_HEAL: f32 = 25.0;
+// Closest a prop may start to either side of a worm, and how many spots to try before giving up on it
+const WORM_CLEARANCE: f32 = 60.0;
+const PLACEMENT_ATTEMPTS: usize = 20;
+
+pub fn spawn_props(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<MatchRng>,
+    terrain: Res<TerrainMap>,
+    worms: Query<&Transform, With<Worm>>,
+) {
+    let worm_xs: Vec<f32> = worms.iter().map(|transform| transform.translation.x).collect();
+    let kinds = std::iter::repeat_n(Some(PropKind::OilBarrel), OIL_BARREL_COUNT)
+        .chain(std::iter::repeat_n(Some(PropKind::HealthCrate), HEALTH_CRATE_COUNT))
+        .chain(std::iter::repeat_n(None, MINE_COUNT)); // None = mine
+
+    for kind in kinds {
+        let Some(position) = random_surface_position(&mut rng, &terrain, &worm_xs) else {
+            continue;
+        };
+
+        match kind {
+            Some(kind) => spawn_prop(&mut commands, &mut meshes, &mut materials, kind, position),
+            None => place_weapon(&mut commands, &mut meshes, &mut materials, WeaponType::Mine, None, position, 1.0),
+        };
+    }
+}
+
+/// Picks a random spot on top of the terrain at least `WORM_CLEARANCE` to either side of
+/// every worm, or None if no such spot turns up. Worms drop straight down onto the ground,
+/// so only their x counts.
+fn random_surface_position(rng: &mut fastrand::Rng, terrain: &TerrainMap, worm_xs: &[f32]) -> Option<Vec3> {
+    for _ in 0..PLACEMENT_ATTEMPTS {
+        let x = rng.usize(terrain.width / 10..terrain.width * 9 / 10);
+        let Some(surface) = terrain.surface_height(x) else {
+            continue;
+        };
+
+        let position = Vec3::new(
+            x as f32 - terrain.width as f32 / 2.0,
+            surface as f32 - terrain.height as f32 / 2.0 + 20.0, // Drop onto the ground
+            0.8,
+        );
+        if worm_xs.iter().all(|worm_x| (worm_x - position.x).abs() >= WORM_CLEARANCE) {
+            return Some(position);
+        }
+    }
+    None
+}
+
+pub fn spawn_prop(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    kind: PropKind,
+    position: Vec3,
+) -> Entity {
+    let size = kind.size();
+
+    commands.spawn((
+        Mesh2d(meshes.add(bevy::math::primitives::Rectangle::new(size.x, size.y))),
+        MeshMaterial2d(materials.add(ColorMaterial::from(kind.color()))),
+        Transform::from_translation(position),
+        Prop {
+            kind,
+            health: kind.max_health(),
+        },
+        RigidBody {
+            velocity: Vec2::ZERO,
+            bounce: 0.1,
+            friction: 0.5,
+            gravity_scale: 1.0,
+        },
+        Collider {
+            radius: size.y / 2.0,
+            is_grounded: false,
+        },
+        TerrainBody,
+    )).id()
+}
+
+fn destroy_damaged_props(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<MatchRng>,
+    mut turn: TurnFlow,
+    prop_query: Query<(Entity, &Transform, &Prop)>,
+) {
+    for (entity, transform, prop) in prop_query.iter() {
+        if prop.health > 0.0 {
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+
+        let (radius, damage) = prop.kind.death_explosion();
+        detonate(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &mut rng,
+            &mut turn,
+            transform.translation,
+            radius,
+            damage,
+            None,
+            DamageCause::Explosion,
+        );
+
+        // Burst barrels spill burning oil
+        if prop.kind == PropKind::OilBarrel {
+            crate::game::fire::spawn_fire(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                &mut rng,
+                transform.translation,
+                BARREL_FIRE_COUNT,
+                None,
+            );
+        }
+    }
+}
+
+fn burn_props(
+    time: Res<Time>,
+    mut damage_events: EventWriter<ApplyDamage>,
+    fire_query: Query<(&Transform, &crate::game::fire::Fire)>,
+    prop_query: Query<(Entity, &Transform, &Collider), With<Prop>>,
+) {
+    for (prop_entity, prop_transform, collider) in prop_query.iter() {
+        for (fire_transform, fire) in fire_query.iter() {
+            let distance = fire_transform.translation.truncate()
+                .distance(prop_transform.translation.truncate());
+            if distance <= fire.radius + collider.radius {
+                damage_events.write(ApplyDamage {
+                    target: prop_entity,
+                    amount: fire.damage_per_second * time.delta_secs(),
+                    source_team: fire.source_team,
+                    cause: DamageCause::Fire,
+                });
+            }
+        }
+    }
+}
+
+fn collect_health_crates(
+    mut commands: Commands,
+    mut healing_events: EventWriter<ApplyHealing>,
+    crate_query: Query<(Entity, &Transform, &Collider, &Prop)>,
+    worm_query: Query<(Entity, &Transform, &Collider, &Worm)>,
+) {
+    for (crate_entity, crate_transform, crate_collider, prop) in crate_query.iter() {
+        if prop.kind != PropKind::HealthCrate || prop.health <= 0.0 {
+            continue;
+        }
+
+        for (worm_entity, worm_transform, worm_collider, worm) in worm_query.iter() {
+            if worm.health - worm.pending_damage <= 0.0 {
+                continue;
+            }
+
+            let distance = crate_transform.translation.truncate()
+                .distance(worm_transform.translation.truncate());
+            if distance <= crate_collider.radius + worm_collider.radius {
+                healing_events.write(ApplyHealing {
+                    target: worm_entity,
+                    amount: HEALTH_CRATE_HEAL,
+                });
+                commands.entity(crate_entity).despawn();
+                break;
+            }
+        }
+    }
+}
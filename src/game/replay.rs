@@ -0,0 +1,398 @@
+use bevy::prelude::*;
+use bevy::ecs::event::{EventRegistry, EventUpdates, ShouldUpdateEvents};
+use bevy::time::TimeSystem;
+use bevy::window::{PresentMode, PrimaryWindow};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use crate::game::events::{IssueWormCommands, WormCommand};
+use crate::game::game_state::GamePhase;
+use crate::game::lifecycle::RestartMatch;
+use crate::game::menu::AppState;
+use crate::game::scheme::MatchSettings;
+use crate::game::storage;
+
+/// Records every match and plays it back. The settings hold the seed, so a replay
+/// only needs each frame's length and the `WormCommand`s sent on it, by players and
+/// AI alike; fed back through the same systems, the rest of the match plays out as it did.
+///
+/// While a replay plays, gameplay only runs on frames that stand in for a recorded
+/// one (see `simulation_running`), which is how it pauses and changes speed.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // Before the events are swapped, so held frames don't drop any
+            .add_systems(First, drive_playback
+                .after(TimeSystem)
+                .before(EventUpdates)
+                .run_if(resource_exists::<ReplayPlayback>))
+            .add_systems(Update, (
+                record_frame
+                    .after(IssueWormCommands)
+                    .run_if(simulation_running.and(resource_exists::<ReplayRecorder>)),
+                play_recorded_commands
+                    .in_set(IssueWormCommands)
+                    .run_if(simulation_running.and(resource_exists::<ReplayPlayback>)),
+                (playback_controls, update_replay_hud).chain()
+                    .run_if(in_state(AppState::InGame).and(resource_exists::<ReplayPlayback>)),
+            ))
+            .add_systems(OnEnter(AppState::InGame), spawn_replay_hud.run_if(resource_exists::<ReplayPlayback>))
+            .add_systems(OnExit(AppState::InGame), end_playback.run_if(resource_exists::<ReplayPlayback>))
+            .add_systems(OnEnter(GamePhase::TurnTransition), mark_turn.run_if(resource_exists::<ReplayRecorder>))
+            .add_systems(OnEnter(GamePhase::GameOver), save_replay.run_if(resource_exists::<ReplayRecorder>));
+    }
+}
+
+const REPLAY_KEY: &str = "replay";
+
+// Bumped whenever `Replay` changes shape; older replays are ignored
+const REPLAY_VERSION: u32 = 2;
+
+const REPLAY_SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+// How far playback may fall behind before it stops trying to catch up, in seconds
+const MAX_CATCH_UP: f32 = 0.25;
+
+/// Run condition for everything that plays the match. The match has to be on, and
+/// during a replay this has to be a frame that plays one of the recorded ones.
+pub fn simulation_running(
+    app_state: Res<State<AppState>>,
+    playback: Option<Res<ReplayPlayback>>,
+) -> bool {
+    *app_state.get() == AppState::InGame
+        && playback.is_none_or(|playback| playback.stepping)
+}
+
+/// Run condition for things a replay shouldn't do again, like adding to the stats.
+pub fn watching_replay(playback: Option<Res<ReplayPlayback>>) -> bool {
+    playback.is_some()
+}
+
+/// A recorded match.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub settings: MatchSettings,
+    /// Length of each frame of the match in nanoseconds
+    pub deltas: Vec<u64>,
+    /// Every command sent to the worms, with the frame it was sent on
+    pub commands: Vec<(u32, WormCommand)>,
+    /// Frames a player took the keyboard over on, leaving the hand-over screen
+    pub hand_overs: Vec<u32>,
+    /// Frame each turn starts on
+    pub turns: Vec<u32>,
+}
+
+impl Replay {
+    pub fn load() -> Option<Self> {
+        let source = storage::load(REPLAY_KEY)?;
+        match ron::from_str::<Replay>(&source) {
+            Ok(replay) if replay.version == REPLAY_VERSION => Some(replay),
+            Ok(replay) => {
+                warn!("Ignoring replay from version {}", replay.version);
+                None
+            }
+            Err(error) => {
+                warn!("Ignoring replay, {}", error);
+                None
+            }
+        }
+    }
+
+    /// Whether there is a replay to watch.
+    pub fn exists() -> bool {
+        storage::load(REPLAY_KEY).is_some()
+    }
+
+    pub fn save(&self) {
+        // Not pretty printed, there is an entry for every frame
+        let result = ron::ser::to_string(self)
+            .map_err(|error| error.to_string())
+            .and_then(|source| storage::save(REPLAY_KEY, &source).map_err(|error| error.to_string()));
+        match result {
+            Ok(()) => info!("Replay saved, {} frames", self.deltas.len()),
+            Err(error) => warn!("Couldn't save the replay, {}", error),
+        }
+    }
+}
+
+/// Records the match being played. `start_match` puts in a fresh one for every new
+/// match; resumed matches aren't recorded since they don't start from the seed.
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    replay: Replay,
+}
+
+impl ReplayRecorder {
+    pub fn new(settings: MatchSettings) -> Self {
+        Self {
+            replay: Replay {
+                version: REPLAY_VERSION,
+                settings,
+                deltas: Vec::new(),
+                commands: Vec::new(),
+                hand_overs: Vec::new(),
+                turns: vec![0],
+            },
+        }
+    }
+
+    /// Notes that the hand-over screen was left on the frame being recorded.
+    pub fn record_hand_over(&mut self) {
+        let frame = self.replay.deltas.len().saturating_sub(1) as u32;
+        self.replay.hand_overs.push(frame);
+    }
+}
+
+/// A replay being watched. The menu puts it in along with the replay's settings.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    /// The next recorded frame to play
+    tick: usize,
+    /// Whether this frame plays one
+    stepping: bool,
+    /// The recorded frame being played
+    frame: usize,
+    /// The match's clock, only moved on by recorded frames
+    sim_time: Time,
+    /// Real time owed to playback at the current speed, in seconds
+    clock: f32,
+    speed: usize,
+    paused: bool,
+    /// Play as fast as possible up to this frame
+    seek_target: Option<usize>,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            tick: 0,
+            stepping: false,
+            frame: 0,
+            sim_time: Time::default(),
+            clock: 0.0,
+            speed: 2,
+            paused: false,
+            seek_target: None,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.tick >= self.replay.deltas.len()
+    }
+
+    /// The turn being played, counting from 1.
+    pub fn turn(&self) -> usize {
+        self.replay.turns.partition_point(|&start| start as usize <= self.tick).max(1)
+    }
+
+    /// The commands sent on the frame being played.
+    fn frame_commands(&self) -> impl Iterator<Item = &WormCommand> {
+        let commands = &self.replay.commands;
+        let start = commands.partition_point(|(frame, _)| (*frame as usize) < self.frame);
+        let end = commands.partition_point(|(frame, _)| (*frame as usize) <= self.frame);
+        commands[start..end].iter().map(|(_, command)| command)
+    }
+
+    /// Whether the recorded player left the hand-over screen on the frame being played.
+    pub fn hand_over_due(&self) -> bool {
+        self.stepping && self.replay.hand_overs.binary_search(&(self.frame as u32)).is_ok()
+    }
+
+    /// Whether to play a recorded frame now, and how long it was.
+    fn next_step(&mut self, real_delta: f32) -> Option<Duration> {
+        let delta = Duration::from_nanos(*self.replay.deltas.get(self.tick)?);
+
+        let seeking = self.seek_target.is_some_and(|target| self.tick < target);
+        if !seeking {
+            self.seek_target = None;
+            if self.paused {
+                return None;
+            }
+
+            self.clock = (self.clock + real_delta * REPLAY_SPEEDS[self.speed]).min(MAX_CATCH_UP);
+            if self.clock < delta.as_secs_f32() {
+                return None;
+            }
+            self.clock -= delta.as_secs_f32();
+        }
+
+        self.frame = self.tick;
+        self.tick += 1;
+        Some(delta)
+    }
+
+    /// Jumps to a frame. Going back starts the match over, so returns whether the
+    /// caller needs to restart it for that.
+    fn seek(&mut self, tick: usize) -> bool {
+        self.seek_target = Some(tick);
+        if tick >= self.tick {
+            return false;
+        }
+
+        self.tick = 0;
+        self.sim_time = Time::default();
+        self.clock = 0.0;
+        true
+    }
+}
+
+fn drive_playback(
+    mut playback: ResMut<ReplayPlayback>,
+    real_time: Res<Time<Real>>,
+    mut time: ResMut<Time>,
+    mut event_registry: ResMut<EventRegistry>,
+) {
+    let step = playback.next_step(real_time.delta_secs());
+    playback.stepping = step.is_some();
+
+    if let Some(delta) = step {
+        playback.sim_time.advance_by(delta);
+        *time = playback.sim_time;
+        event_registry.should_update = ShouldUpdateEvents::Always;
+    } else {
+        // Hold on to this frame's events until the match moves again
+        event_registry.should_update = ShouldUpdateEvents::Waiting;
+    }
+}
+
+fn play_recorded_commands(
+    playback: Res<ReplayPlayback>,
+    mut worm_commands: EventWriter<WormCommand>,
+) {
+    for command in playback.frame_commands() {
+        worm_commands.write(command.clone());
+    }
+}
+
+fn record_frame(
+    mut recorder: ResMut<ReplayRecorder>,
+    time: Res<Time>,
+    mut worm_commands: EventReader<WormCommand>,
+) {
+    let frame = recorder.replay.deltas.len() as u32;
+    recorder.replay.deltas.push(time.delta().as_nanos() as u64);
+
+    for command in worm_commands.read() {
+        recorder.replay.commands.push((frame, command.clone()));
+    }
+}
+
+fn mark_turn(mut recorder: ResMut<ReplayRecorder>) {
+    let tick = recorder.replay.deltas.len() as u32;
+    recorder.replay.turns.push(tick);
+}
+
+fn save_replay(mut commands: Commands, recorder: Res<ReplayRecorder>) {
+    recorder.replay.save();
+    commands.remove_resource::<ReplayRecorder>();
+}
+
+fn playback_controls(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut restart_events: EventWriter<RestartMatch>,
+) {
+    let pause = keys.just_pressed(KeyCode::KeyP);
+    let slower = keys.just_pressed(KeyCode::BracketLeft);
+    let faster = keys.just_pressed(KeyCode::BracketRight);
+    let previous_turn = keys.just_pressed(KeyCode::Comma);
+    let next_turn = keys.just_pressed(KeyCode::Period);
+    let leave = keys.just_pressed(KeyCode::Escape);
+
+    if leave {
+        next_state.set(AppState::MainMenu);
+        return;
+    }
+
+    if pause {
+        playback.paused = !playback.paused;
+    }
+    if slower {
+        playback.speed = playback.speed.saturating_sub(1);
+    }
+    if faster {
+        playback.speed = (playback.speed + 1).min(REPLAY_SPEEDS.len() - 1);
+    }
+
+    if previous_turn || next_turn {
+        let turn = playback.turn();
+        let target = if next_turn { turn + 1 } else { turn.saturating_sub(1).max(1) };
+        if let Some(&start) = playback.replay.turns.get(target - 1) {
+            // Going back plays the match again from the start on the same map
+            if playback.seek(start as usize) {
+                restart_events.write(RestartMatch { new_map: false });
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct ReplayHud;
+
+fn spawn_replay_hud(
+    mut commands: Commands,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            bottom: Val::Px(10.0),
+            ..default()
+        },
+        ReplayHud,
+        StateScoped(AppState::InGame),
+    ));
+
+    // Past vsync, so fast playback isn't held to the display's refresh rate
+    if let Ok(mut window) = window_query.single_mut() {
+        window.present_mode = PresentMode::AutoNoVsync;
+    }
+}
+
+fn update_replay_hud(
+    playback: Res<ReplayPlayback>,
+    mut hud_query: Query<&mut Text, With<ReplayHud>>,
+) {
+    let status = if playback.finished() {
+        "  finished"
+    } else if playback.paused {
+        "  paused"
+    } else {
+        ""
+    };
+
+    for mut text in hud_query.iter_mut() {
+        text.0 = format!(
+            "Replay: turn {} of {}, {}x{}\nP pause, [ ] speed, , . previous/next turn, WASD camera, Esc leave",
+            playback.turn(),
+            playback.replay.turns.len(),
+            REPLAY_SPEEDS[playback.speed],
+            status,
+        );
+    }
+}
+
+/// Puts the game back as it was before the replay.
+fn end_playback(
+    mut commands: Commands,
+    mut event_registry: ResMut<EventRegistry>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    commands.remove_resource::<ReplayPlayback>();
+    event_registry.should_update = ShouldUpdateEvents::Waiting;
+    if let Ok(mut window) = window_query.single_mut() {
+        window.present_mode = Window::default().present_mode;
+    }
+}
@@ -0,0 +1,432 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::game::game_state::{GameState, GamePhase, RoundTimer};
+use crate::game::lifecycle::MatchRng;
+use crate::game::ai::AIController;
+use crate::game::replay::{simulation_running, watching_replay};
+use crate::game::scheme::{MatchSettings, TeamController};
+use crate::game::profiles::GravestoneStyle;
+use crate::game::terrain::TerrainMap;
+use crate::game::worm::{Worm, Gravestone, spawn_worm, spawn_gravestone};
+use crate::game::weapons::{WeaponInventory, WeaponType, WindSystem};
+use crate::game::props::{Prop, PropKind, spawn_prop};
+use crate::game::placed::{Deployable, ProximityTrigger, place_weapon};
+use crate::game::water::{WaterLevel, spawn_water_sheet};
+use crate::game::fire::{Fire, spawn_flame};
+use crate::game::tools::{GirderVisual, spawn_girder_visual};
+use crate::game::stats::{MatchStats, TeamRecord};
+use crate::game::storage;
+
+/// Puts a long match away to finish later. F5 asks for a save, which is written
+/// once the turn has ended and before the next team plays, so nothing is in flight.
+pub struct SaveGamePlugin;
+
+impl Plugin for SaveGamePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<SaveRequest>()
+            .add_systems(Update, (
+                request_save.run_if(not(watching_replay)),
+                write_save.run_if(in_state(GamePhase::TurnTransition)),
+            ).chain().run_if(simulation_running))
+            // A finished match can't be continued
+            .add_systems(OnEnter(GamePhase::GameOver), discard_save.run_if(not(watching_replay)));
+    }
+}
+
+const SAVE_KEY: &str = "match";
+
+// Bumped whenever `SavedMatch` changes shape; older saves are ignored
+const SAVE_VERSION: u32 = 4;
+
+#[derive(Resource, Default)]
+pub struct SaveRequest {
+    /// Save as soon as the current turn is over
+    pub pending: bool,
+}
+
+/// A saved match waiting to be picked up. `start_match` restores it instead of
+/// spawning a fresh line-up when this is present.
+#[derive(Resource)]
+pub struct ResumeMatch(pub SavedMatch);
+
+/// Everything about a match between two turns.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedMatch {
+    pub version: u32,
+    pub settings: MatchSettings,
+    /// `TerrainMap::encode` as base64
+    pub terrain: String,
+    /// The team about to play
+    pub current_player: u32,
+    /// Per team, in team order; picks which of its worms goes next
+    pub turns_taken: Vec<u32>,
+    pub worms: Vec<SavedWorm>,
+    pub gravestones: Vec<SavedGravestone>,
+    pub props: Vec<SavedProp>,
+    /// Mines still lying armed on the map
+    pub mines: Vec<SavedMine>,
+    /// Napalm and oil still burning, which outlasts the turn that lit it
+    pub fires: Vec<SavedFire>,
+    /// Placed girders, to draw again; the terrain already holds them
+    pub girders: Vec<SavedGirder>,
+    pub wind: f32,
+    pub weapons: Vec<WeaponType>,
+    /// Per team, in team order; the weapon each team has picked
+    pub team_weapons: Vec<usize>,
+    pub round_time_left: f32,
+    pub rounds_played: u32,
+    pub sudden_death: bool,
+    pub water_level: f32,
+    pub water_target: f32,
+    pub water_rising: bool,
+    pub stats: Vec<TeamRecord>,
+    /// State of `MatchRng`, so the match carries on as it would have
+    pub rng: u64,
+    /// State of the AI's own generator, so it aims as it would have
+    pub ai_rng: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedWorm {
+    pub name: String,
+    pub team: u32,
+    pub health: f32,
+    pub max_health: f32,
+    pub facing: f32,
+    pub position: [f32; 3],
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedGravestone {
+    pub team: u32,
+    pub position: [f32; 3],
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedProp {
+    pub kind: PropKind,
+    pub health: f32,
+    pub position: [f32; 3],
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedMine {
+    pub owner_team: Option<u32>,
+    pub position: [f32; 3],
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedFire {
+    pub source_team: Option<u32>,
+    pub position: [f32; 3],
+    pub velocity: [f32; 2],
+    pub stuck: bool,
+    /// How long the flame burns in all and how long it has been burning, in seconds
+    pub burn_time: f32,
+    pub burned: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedGirder {
+    pub position: [f32; 2],
+    /// In radians
+    pub angle: f32,
+}
+
+impl SavedMatch {
+    pub fn load() -> Option<Self> {
+        let source = storage::load(SAVE_KEY)?;
+        match ron::from_str::<SavedMatch>(&source) {
+            Ok(saved) if saved.version == SAVE_VERSION => Some(saved),
+            Ok(saved) => {
+                warn!("Ignoring saved match from version {}", saved.version);
+                None
+            }
+            Err(error) => {
+                warn!("Ignoring saved match, {}", error);
+                None
+            }
+        }
+    }
+
+    /// The saved terrain, if it can still be read.
+    pub fn terrain(&self) -> Option<TerrainMap> {
+        let Some(bytes) = storage::decode_bytes(&self.terrain) else {
+            warn!("The saved terrain isn't valid base64");
+            return None;
+        };
+        TerrainMap::decode(&bytes)
+            .map_err(|error| warn!("Couldn't read the saved terrain, {}", error))
+            .ok()
+    }
+
+    /// Whether there is a match to continue.
+    pub fn exists() -> bool {
+        storage::load(SAVE_KEY).is_some()
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|source| storage::save(SAVE_KEY, &source).map_err(|error| error.to_string()));
+        match result {
+            Ok(()) => info!("Match saved"),
+            Err(error) => warn!("Couldn't save the match, {}", error),
+        }
+    }
+}
+
+fn request_save(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut request: ResMut<SaveRequest>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F5) && !request.pending {
+        request.pending = true;
+        info!("The match will be saved when this turn ends");
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_save(
+    mut request: ResMut<SaveRequest>,
+    settings: Res<MatchSettings>,
+    terrain: Res<TerrainMap>,
+    game_state: Res<GameState>,
+    round_timer: Res<RoundTimer>,
+    wind: Res<WindSystem>,
+    water: Res<WaterLevel>,
+    inventory: Res<WeaponInventory>,
+    stats: Res<MatchStats>,
+    (rng, ai_controller): (Res<MatchRng>, Res<AIController>),
+    worm_query: Query<(Entity, &Transform, &Worm)>,
+    gravestone_query: Query<(&Transform, &Gravestone)>,
+    prop_query: Query<(&Transform, &Prop)>,
+    mine_query: Query<(&Transform, &Deployable, &ProximityTrigger)>,
+    fire_query: Query<(&Transform, &Fire)>,
+    girder_query: Query<&Transform, With<GirderVisual>>,
+) {
+    if !request.pending {
+        return;
+    }
+    request.pending = false;
+
+    // Spawned back in the same order so each team's worms keep their turn order
+    let mut worms: Vec<_> = worm_query.iter().collect();
+    worms.sort_by_key(|(entity, _, _)| *entity);
+
+    let saved = SavedMatch {
+        version: SAVE_VERSION,
+        settings: settings.clone(),
+        terrain: storage::encode_bytes(&terrain.encode()),
+        current_player: game_state.current_player,
+        turns_taken: game_state.teams.iter().map(|team| team.turns_taken).collect(),
+        worms: worms.into_iter()
+            .map(|(_, transform, worm)| SavedWorm {
+                name: worm.name.clone(),
+                team: worm.team,
+                health: worm.health,
+                max_health: worm.max_health,
+                facing: worm.facing,
+                position: transform.translation.to_array(),
+            })
+            .collect(),
+        gravestones: gravestone_query.iter()
+            .map(|(transform, gravestone)| SavedGravestone {
+                team: gravestone.team,
+                position: transform.translation.to_array(),
+            })
+            .collect(),
+        props: prop_query.iter()
+            .map(|(transform, prop)| SavedProp {
+                kind: prop.kind,
+                health: prop.health,
+                position: transform.translation.to_array(),
+            })
+            .collect(),
+        mines: mine_query.iter()
+            .filter(|(_, _, trigger)| !trigger.triggered)
+            .map(|(transform, deployable, _)| SavedMine {
+                owner_team: deployable.owner_team,
+                position: transform.translation.to_array(),
+            })
+            .collect(),
+        fires: fire_query.iter()
+            .map(|(transform, fire)| SavedFire {
+                source_team: fire.source_team,
+                position: transform.translation.to_array(),
+                velocity: fire.velocity.to_array(),
+                stuck: fire.stuck,
+                burn_time: fire.burn_timer.duration().as_secs_f32(),
+                burned: fire.burn_timer.elapsed_secs(),
+            })
+            .collect(),
+        girders: girder_query.iter()
+            .map(|transform| SavedGirder {
+                position: transform.translation.truncate().to_array(),
+                angle: transform.rotation.to_euler(EulerRot::XYZ).2,
+            })
+            .collect(),
+        wind: wind.force.x,
+        weapons: inventory.weapons.clone(),
+        team_weapons: inventory.picks(game_state.teams.len()),
+        round_time_left: round_timer.time_left,
+        rounds_played: round_timer.rounds_played,
+        sudden_death: round_timer.sudden_death,
+        water_level: water.level,
+        water_target: water.target,
+        water_rising: water.rising,
+        stats: stats.teams.clone(),
+        rng: rng.get_seed(),
+        ai_rng: ai_controller.rng.get_seed(),
+    };
+
+    saved.save();
+}
+
+fn discard_save() {
+    storage::remove(SAVE_KEY);
+}
+
+/// Puts the saved worms, objects and match state back. Run by `start_match` in place
+/// of spawning a fresh line-up, after the terrain and default resources are in.
+#[allow(clippy::too_many_arguments)]
+pub fn restore_match(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    resume: Res<ResumeMatch>,
+    terrain: Res<TerrainMap>,
+    mut game_state: ResMut<GameState>,
+    mut round_timer: ResMut<RoundTimer>,
+    mut wind: ResMut<WindSystem>,
+    mut water: ResMut<WaterLevel>,
+    mut inventory: ResMut<WeaponInventory>,
+    mut stats: ResMut<MatchStats>,
+    mut rng: ResMut<MatchRng>,
+    mut ai_controller: ResMut<AIController>,
+) {
+    let saved = &resume.0;
+
+    for worm in saved.worms.iter() {
+        let team = saved.settings.teams.get(worm.team as usize);
+        spawn_worm(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            Worm {
+                name: worm.name.clone(),
+                team: worm.team,
+                health: worm.health,
+                max_health: worm.max_health,
+                facing: worm.facing,
+                ..default()
+            },
+            Vec3::from_array(worm.position),
+            team.map_or(Color::WHITE, |team| team.color()),
+            team.map_or(TeamController::AI, |team| team.controller),
+        );
+    }
+
+    for gravestone in saved.gravestones.iter() {
+        let style = saved.settings.teams.get(gravestone.team as usize)
+            .map(|team| team.gravestone)
+            .unwrap_or(GravestoneStyle::Headstone);
+        spawn_gravestone(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            style,
+            gravestone.team,
+            Vec3::from_array(gravestone.position),
+            Vec2::ZERO,
+        );
+    }
+
+    for prop in saved.props.iter() {
+        let health = prop.health;
+        let entity = spawn_prop(&mut commands, &mut meshes, &mut materials, prop.kind, Vec3::from_array(prop.position));
+        commands.entity(entity)
+            .entry::<Prop>()
+            .and_modify(move |mut prop| prop.health = health);
+    }
+
+    for mine in saved.mines.iter() {
+        // Placed without an owner so it isn't counted as a fresh shot, then handed back
+        let owner_team = mine.owner_team;
+        let entity = place_weapon(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            WeaponType::Mine,
+            None,
+            Vec3::from_array(mine.position),
+            1.0,
+        );
+        commands.entity(entity)
+            .entry::<Deployable>()
+            .and_modify(move |mut deployable| deployable.owner_team = owner_team);
+    }
+
+    for saved_fire in saved.fires.iter() {
+        let mut fire = Fire::new(Vec2::from_array(saved_fire.velocity), saved_fire.burn_time, saved_fire.source_team);
+        fire.stuck = saved_fire.stuck;
+        fire.burn_timer.set_elapsed(std::time::Duration::from_secs_f32(saved_fire.burned));
+        spawn_flame(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            Vec3::from_array(saved_fire.position),
+            Color::srgb(1.0, 0.55, 0.0),
+            fire,
+        );
+    }
+
+    for girder in saved.girders.iter() {
+        spawn_girder_visual(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            Vec2::from_array(girder.position),
+            girder.angle,
+        );
+    }
+
+    game_state.current_player = saved.current_player;
+    for team in game_state.teams.iter_mut() {
+        team.turns_taken = saved.turns_taken.get(team.id as usize).copied().unwrap_or(0);
+        team.worms_alive = saved.worms.iter()
+            .filter(|worm| worm.team == team.id && worm.health > 0.0)
+            .count() as u32;
+    }
+
+    round_timer.time_left = saved.round_time_left;
+    round_timer.rounds_played = saved.rounds_played;
+    round_timer.sudden_death = saved.sudden_death;
+
+    wind.force = Vec2::new(saved.wind, 0.0);
+
+    inventory.weapons = saved.weapons.clone();
+    let last_weapon = inventory.weapons.len().saturating_sub(1);
+    inventory.team_weapons = saved.team_weapons.iter()
+        .map(|&weapon| weapon.min(last_weapon))
+        .collect();
+    inventory.team = saved.current_player;
+    inventory.current_weapon = inventory.team_weapons.get(saved.current_player as usize).copied().unwrap_or(0);
+
+    water.level = saved.water_level;
+    water.target = saved.water_target;
+    water.rising = saved.water_rising;
+    if water.rising {
+        spawn_water_sheet(&mut commands, &mut meshes, &mut materials, &terrain, water.level);
+    }
+
+    stats.teams = saved.stats.clone();
+    stats.teams.resize(game_state.teams.len(), TeamRecord::default());
+
+    rng.seed(saved.rng);
+    ai_controller.rng.seed(saved.ai_rng);
+    info!("Resumed a saved match");
+
+    commands.remove_resource::<ResumeMatch>();
+}
@@ -0,0 +1,307 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::game::profiles::{TeamProfiles, TeamProfile, GravestoneStyle};
+use crate::game::damage::FalloffCurve;
+
+/// Picks the rules for the match. Added before the other game plugins so that
+/// they can read `GameScheme` while building, and after `ProfilePlugin`.
+pub struct SchemePlugin;
+
+impl Plugin for SchemePlugin {
+    fn build(&self, app: &mut App) {
+        let settings = MatchSettings::new(initial_scheme(), app.world().resource::<TeamProfiles>());
+        info!("Playing with the {} scheme", settings.scheme.name);
+
+        app
+            .insert_resource(settings.scheme.clone())
+            .insert_resource(MatchSetup(settings.clone()))
+            .insert_resource(settings);
+    }
+}
+
+/// The rules of a match. Every tunable that changes how a game plays lives here.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameScheme {
+    pub name: String,
+    /// Seconds each team gets per turn
+    pub turn_time: f32,
+    /// Seconds to get to cover after firing
+    pub retreat_time: f32,
+    pub worm_health: f32,
+    /// Landing speed a worm can take without getting hurt
+    pub fall_damage_threshold: f32,
+    /// Damage per unit of landing speed over the threshold
+    pub fall_damage_per_speed: f32,
+    /// Wind is picked between -max_wind and +max_wind
+    pub max_wind: f32,
+    /// Full rounds played before sudden death starts, 0 for no limit
+    pub rounds_until_sudden_death: u32,
+    /// Seconds of play before sudden death starts
+    pub round_time: f32,
+    pub sudden_death: SuddenDeathEffect,
+    /// How far the water comes up each turn once it is rising
+    pub water_rise_per_turn: f32,
+    /// How explosion damage drops off towards the edge of the blast
+    #[serde(default)]
+    pub explosion_falloff: FalloffCurve,
+}
+
+/// What happens when sudden death starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SuddenDeathEffect {
+    /// Every worm drops to 1 HP
+    OneHealth,
+    /// The water starts rising every turn
+    RisingWater,
+    Both,
+}
+
+impl SuddenDeathEffect {
+    pub fn sets_one_health(&self) -> bool {
+        matches!(self, SuddenDeathEffect::OneHealth | SuddenDeathEffect::Both)
+    }
+
+    pub fn raises_water(&self) -> bool {
+        matches!(self, SuddenDeathEffect::RisingWater | SuddenDeathEffect::Both)
+    }
+}
+
+const CLASSIC_SCHEME: &str = include_str!("../../assets/schemes/classic.ron");
+const PRO_SCHEME: &str = include_str!("../../assets/schemes/pro.ron");
+const FORT_SCHEME: &str = include_str!("../../assets/schemes/fort.ron");
+
+impl Default for GameScheme {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+impl GameScheme {
+    pub fn classic() -> Self {
+        Self::from_ron(CLASSIC_SCHEME).expect("built-in Classic scheme is valid")
+    }
+
+    pub fn pro() -> Self {
+        Self::from_ron(PRO_SCHEME).expect("built-in Pro scheme is valid")
+    }
+
+    pub fn fort() -> Self {
+        Self::from_ron(FORT_SCHEME).expect("built-in Fort scheme is valid")
+    }
+
+    /// All schemes that ship with the game.
+    pub fn presets() -> Vec<GameScheme> {
+        vec![Self::classic(), Self::pro(), Self::fort()]
+    }
+
+    /// Looks up a built-in scheme by name, ignoring case.
+    pub fn preset(name: &str) -> Option<GameScheme> {
+        Self::presets().into_iter()
+            .find(|scheme| scheme.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, SchemeError> {
+        ron::from_str(source).map_err(SchemeError::Parse)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: &std::path::Path) -> Result<Self, SchemeError> {
+        let source = std::fs::read_to_string(path).map_err(SchemeError::Io)?;
+        Self::from_ron(&source)
+    }
+}
+
+#[derive(Debug)]
+pub enum SchemeError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for SchemeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemeError::Io(error) => write!(f, "couldn't read scheme: {}", error),
+            SchemeError::Parse(error) => write!(f, "invalid scheme: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for SchemeError {}
+
+/// Everything needed to set up the same match again: the rules, the line-up, the map
+/// and the random seed. Replays and network peers share this instead of their local settings.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MatchSettings {
+    pub seed: u64,
+    pub scheme: GameScheme,
+    pub teams: Vec<TeamSetup>,
+    pub worms_per_team: u32,
+    pub ai_difficulty: AIDifficulty,
+    pub map: MapSource,
+}
+
+/// The settings being put together on the setup screen. Starting a match copies them
+/// into `MatchSettings`, so resuming a save or watching a replay leaves them alone.
+#[derive(Resource, Clone, Debug, Deref, DerefMut)]
+pub struct MatchSetup(pub MatchSettings);
+
+pub const MIN_TEAMS: usize = 2;
+pub const MAX_TEAMS: usize = 8;
+pub const MAX_WORMS_PER_TEAM: u32 = 4;
+
+impl MatchSettings {
+    /// One human against one AI worm on a generated map, using the first two profiles.
+    pub fn new(scheme: GameScheme, profiles: &TeamProfiles) -> Self {
+        Self {
+            seed: fastrand::u64(..),
+            scheme,
+            teams: vec![
+                TeamSetup::for_slot(0, profiles, TeamController::Human),
+                TeamSetup::for_slot(1, profiles, TeamController::AI),
+            ],
+            worms_per_team: 1,
+            ai_difficulty: AIDifficulty::Normal,
+            map: MapSource::Generated,
+        }
+    }
+
+    /// Same line-up and rules on a fresh roll of the dice.
+    pub fn reroll(&mut self) {
+        self.seed = fastrand::u64(..);
+    }
+}
+
+/// Default team colours, handed out in order. Stored as sRGB so settings can be saved.
+pub const TEAM_COLORS: [[f32; 3]; MAX_TEAMS] = [
+    [0.2, 0.8, 0.2],
+    [0.8, 0.2, 0.2],
+    [0.2, 0.4, 0.9],
+    [0.9, 0.8, 0.2],
+    [0.8, 0.3, 0.8],
+    [0.2, 0.8, 0.8],
+    [0.9, 0.5, 0.1],
+    [0.9, 0.9, 0.9],
+];
+
+/// A team as it plays in this match. Copied from a `TeamProfile` so the settings
+/// stand on their own when shared.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TeamSetup {
+    pub name: String,
+    pub worm_names: Vec<String>,
+    pub color: [f32; 3],
+    pub gravestone: GravestoneStyle,
+    pub controller: TeamController,
+    /// Teams with the same alliance win together. Everyone in their own alliance is a free-for-all.
+    pub alliance: u32,
+}
+
+impl TeamSetup {
+    /// The `index`th team of the line-up, playing as the `index`th profile in an alliance of its own.
+    pub fn for_slot(index: usize, profiles: &TeamProfiles, controller: TeamController) -> Self {
+        let mut setup = Self {
+            name: format!("Team {}", index + 1),
+            worm_names: Vec::new(),
+            color: TEAM_COLORS[index % MAX_TEAMS],
+            gravestone: GravestoneStyle::Headstone,
+            controller,
+            alliance: index as u32,
+        };
+        if !profiles.profiles.is_empty() {
+            setup.apply_profile(&profiles.profiles[index % profiles.profiles.len()]);
+        }
+        setup
+    }
+
+    pub fn apply_profile(&mut self, profile: &TeamProfile) {
+        self.name = profile.name.clone();
+        self.worm_names = profile.worm_names.clone();
+        self.color = profile.color;
+        self.gravestone = profile.gravestone;
+    }
+
+    pub fn worm_name(&self, index: usize) -> String {
+        if self.worm_names.is_empty() {
+            format!("Worm {}", index + 1)
+        } else {
+            self.worm_names[index % self.worm_names.len()].clone()
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        Color::srgb(self.color[0], self.color[1], self.color[2])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TeamController {
+    /// Someone at this machine
+    Human,
+    AI,
+    /// Driven from elsewhere, e.g. over the network; nothing local moves its worms.
+    /// Not offered in the setup yet, as nothing sends its commands.
+    Remote,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AIDifficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl AIDifficulty {
+    pub const ALL: [AIDifficulty; 3] = [AIDifficulty::Easy, AIDifficulty::Normal, AIDifficulty::Hard];
+
+    /// How far off the AI's aim can be either way, in degrees
+    pub fn aim_error(&self) -> f32 {
+        match self {
+            AIDifficulty::Easy => 25.0,
+            AIDifficulty::Normal => 15.0,
+            AIDifficulty::Hard => 5.0,
+        }
+    }
+
+    /// Seconds the AI waits before lining up a shot
+    pub fn thinking_time(&self) -> f32 {
+        match self {
+            AIDifficulty::Easy => 3.0,
+            AIDifficulty::Normal => 2.0,
+            AIDifficulty::Hard => 1.0,
+        }
+    }
+}
+
+/// Where the landscape comes from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MapSource {
+    /// Rolling hills generated from the match seed
+    Generated,
+    /// One of the images in `assets/maps/`, opaque pixels are ground
+    Image(String),
+}
+
+/// On native builds `WORMS_SCHEME` can name a preset or point at a `.ron` scheme file.
+#[cfg(not(target_arch = "wasm32"))]
+fn initial_scheme() -> GameScheme {
+    let Ok(choice) = std::env::var("WORMS_SCHEME") else {
+        return GameScheme::default();
+    };
+
+    if let Some(scheme) = GameScheme::preset(&choice) {
+        return scheme;
+    }
+
+    match GameScheme::load(std::path::Path::new(&choice)) {
+        Ok(scheme) => scheme,
+        Err(error) => {
+            warn!("Falling back to the Classic scheme, {}", error);
+            GameScheme::default()
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn initial_scheme() -> GameScheme {
+    GameScheme::default()
+}
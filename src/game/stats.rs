@@ -0,0 +1,318 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::game::events::{DamageCause, DamageDealt, ProjectileFired, WormDied};
+use crate::game::game_state::{GameState, GamePhase, MatchOutcome};
+use crate::game::replay::{simulation_running, watching_replay};
+use crate::game::storage;
+use crate::game::weapons::WeaponType;
+use crate::game::worm::ActiveWorm;
+
+/// Keeps score. Each match is tallied from the damage, death and firing events,
+/// then added to the all-time record of every team when it ends.
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(StatsBook::load())
+            .init_resource::<MatchStats>()
+            .add_systems(Update, (
+                track_shots,
+                track_damage,
+                track_kills,
+            ).chain().run_if(simulation_running))
+            .add_systems(OnEnter(GamePhase::TurnTransition), close_shots)
+            .add_systems(OnEnter(GamePhase::GameOver), record_match.run_if(not(watching_replay)));
+    }
+}
+
+const STATS_KEY: &str = "stats";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TeamRecord {
+    pub matches: u32,
+    pub wins: u32,
+    pub kills: u32,
+    pub damage_dealt: f32,
+    pub self_damage: f32,
+    /// Keyed by the weapon's display name, which stays put if the enum variant is renamed
+    pub weapons: BTreeMap<String, WeaponRecord>,
+    /// Furthest a shot has travelled to hurt an enemy
+    pub longest_shot: f32,
+    #[serde(default)]
+    pub worms_lost: u32,
+    /// Most enemy damage done by a single shot
+    #[serde(default)]
+    pub biggest_hit: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeaponRecord {
+    /// Turns the weapon was used in
+    pub shots: u32,
+    /// Of those, the ones that hurt an enemy
+    pub hits: u32,
+}
+
+impl WeaponRecord {
+    pub fn accuracy(&self) -> f32 {
+        if self.shots == 0 {
+            0.0
+        } else {
+            self.hits as f32 / self.shots as f32
+        }
+    }
+}
+
+impl TeamRecord {
+    pub fn shots(&self) -> u32 {
+        self.weapons.values().map(|weapon| weapon.shots).sum()
+    }
+
+    pub fn accuracy(&self) -> f32 {
+        let shots = self.shots();
+        if shots == 0 {
+            0.0
+        } else {
+            self.weapons.values().map(|weapon| weapon.hits).sum::<u32>() as f32 / shots as f32
+        }
+    }
+
+    fn add(&mut self, other: &TeamRecord) {
+        self.matches += other.matches;
+        self.wins += other.wins;
+        self.kills += other.kills;
+        self.damage_dealt += other.damage_dealt;
+        self.self_damage += other.self_damage;
+        self.longest_shot = self.longest_shot.max(other.longest_shot);
+        self.worms_lost += other.worms_lost;
+        self.biggest_hit = self.biggest_hit.max(other.biggest_hit);
+        for (weapon, record) in other.weapons.iter() {
+            let total = self.weapons.entry(weapon.clone()).or_default();
+            total.shots += record.shots;
+            total.hits += record.hits;
+        }
+    }
+}
+
+/// All-time records, keyed by team name so they follow the team profile.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatsBook {
+    pub teams: BTreeMap<String, TeamRecord>,
+}
+
+impl StatsBook {
+    pub fn load() -> Self {
+        let Some(source) = storage::load(STATS_KEY) else {
+            return Self::default();
+        };
+        ron::from_str(&source).unwrap_or_else(|error| {
+            warn!("Ignoring saved stats, {}", error);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|source| storage::save(STATS_KEY, &source).map_err(|error| error.to_string()));
+        if let Err(error) = result {
+            warn!("Couldn't save stats, {}", error);
+        }
+    }
+}
+
+/// The match being played, one record per team in team order.
+#[derive(Resource, Default)]
+pub struct MatchStats {
+    pub teams: Vec<TeamRecord>,
+    /// Weapons used this turn and the worm position they were fired from, to tell hits from misses
+    open_shots: Vec<OpenShot>,
+}
+
+struct OpenShot {
+    team: u32,
+    weapon: WeaponType,
+    origin: Vec2,
+    hit: bool,
+    damage: f32,
+}
+
+impl MatchStats {
+    pub fn new(team_count: usize) -> Self {
+        Self {
+            teams: vec![TeamRecord::default(); team_count],
+            open_shots: Vec::new(),
+        }
+    }
+
+    fn team_mut(&mut self, team: u32) -> Option<&mut TeamRecord> {
+        self.teams.get_mut(team as usize)
+    }
+
+    /// Light-hearted titles for the results screen, as (award, team, what for).
+    /// Awards nobody earned are left out.
+    pub fn awards(&self) -> Vec<(&'static str, u32, String)> {
+        let mut awards = Vec::new();
+
+        let leader = |value: fn(&TeamRecord) -> f32| {
+            self.teams.iter().enumerate()
+                .map(|(team, record)| (team as u32, value(record)))
+                .filter(|(_, value)| *value > 0.0)
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        };
+
+        if let Some((team, damage)) = leader(|record| record.biggest_hit) {
+            awards.push(("Biggest Hit", team, format!("{:.0} damage in one shot", damage)));
+        }
+        if let Some((team, damage)) = leader(|record| record.self_damage) {
+            awards.push(("Own Goal", team, format!("{:.0} self-inflicted damage", damage)));
+        }
+        if let Some((team, distance)) = leader(|record| record.longest_shot) {
+            awards.push(("Sharpshooter", team, format!("a hit from {:.0} away", distance)));
+        }
+
+        awards
+    }
+}
+
+fn track_shots(
+    mut stats: ResMut<MatchStats>,
+    mut fired_events: EventReader<ProjectileFired>,
+    active_worm: Query<&Transform, With<ActiveWorm>>,
+) {
+    // Distance counts from the worm that fired, not from where the shot appeared.
+    // Strike bombs are released high above the map.
+    let worm_position = active_worm.single().ok().map(|transform| transform.translation.truncate());
+
+    for event in fired_events.read() {
+        let Some(team) = event.team else {
+            continue;
+        };
+
+        // Every bomb of a strike counts as the one shot
+        let already_open = stats.open_shots.iter()
+            .any(|shot| shot.team == team && shot.weapon == event.weapon);
+        if already_open {
+            continue;
+        }
+
+        stats.open_shots.push(OpenShot {
+            team,
+            weapon: event.weapon.clone(),
+            origin: worm_position.unwrap_or(event.position),
+            hit: false,
+            damage: 0.0,
+        });
+        if let Some(record) = stats.team_mut(team) {
+            record.weapons.entry(event.weapon.name().to_string()).or_default().shots += 1;
+        }
+    }
+}
+
+fn track_damage(
+    mut stats: ResMut<MatchStats>,
+    game_state: Res<GameState>,
+    mut dealt_events: EventReader<DamageDealt>,
+    transform_query: Query<&Transform>,
+) {
+    for event in dealt_events.read() {
+        let (Some(source), Some(target)) = (event.source_team, event.target_team) else {
+            continue;
+        };
+
+        if source == target {
+            if let Some(record) = stats.team_mut(source) {
+                record.self_damage += event.amount;
+            }
+            continue;
+        }
+
+        // Friendly fire between allies doesn't count either way
+        if game_state.are_allies(source, target) {
+            continue;
+        }
+
+        if let Some(record) = stats.team_mut(source) {
+            record.damage_dealt += event.amount;
+        }
+
+        let DamageCause::Weapon(weapon) = &event.cause else {
+            continue;
+        };
+
+        let target_position = transform_query.get(event.target)
+            .map(|transform| transform.translation.truncate())
+            .ok();
+        let Some(shot) = stats.open_shots.iter_mut()
+            .find(|shot| shot.team == source && shot.weapon == *weapon) else {
+            continue;
+        };
+
+        let first_hit = !shot.hit;
+        shot.hit = true;
+        shot.damage += event.amount;
+        let shot_damage = shot.damage;
+        let distance = target_position.map_or(0.0, |position| position.distance(shot.origin));
+
+        if let Some(record) = stats.team_mut(source) {
+            if first_hit {
+                record.weapons.entry(weapon.name().to_string()).or_default().hits += 1;
+            }
+            record.longest_shot = record.longest_shot.max(distance);
+            record.biggest_hit = record.biggest_hit.max(shot_damage);
+        }
+    }
+}
+
+fn track_kills(
+    mut stats: ResMut<MatchStats>,
+    game_state: Res<GameState>,
+    mut died_events: EventReader<WormDied>,
+) {
+    for event in died_events.read() {
+        if let Some(record) = stats.team_mut(event.team) {
+            record.worms_lost += 1;
+        }
+
+        let Some(killer) = event.killer_team else {
+            continue;
+        };
+        if game_state.are_allies(killer, event.team) {
+            continue;
+        }
+        if let Some(record) = stats.team_mut(killer) {
+            record.kills += 1;
+        }
+    }
+}
+
+fn close_shots(mut stats: ResMut<MatchStats>) {
+    stats.open_shots.clear();
+}
+
+/// Adds the finished match to every team's all-time record.
+fn record_match(
+    mut stats: ResMut<MatchStats>,
+    mut book: ResMut<StatsBook>,
+    game_state: Res<GameState>,
+) {
+    for team in game_state.teams.iter() {
+        let won = match game_state.winner {
+            Some(MatchOutcome::Winner(winner)) => winner == team.id,
+            Some(MatchOutcome::Alliance(alliance)) => alliance == team.alliance,
+            _ => false,
+        };
+
+        let Some(record) = stats.team_mut(team.id) else {
+            continue;
+        };
+        record.matches = 1;
+        record.wins = won as u32;
+
+        book.teams.entry(team.name.clone()).or_default().add(record);
+    }
+
+    book.save();
+}
@@ -0,0 +1,122 @@
+// Small text blobs that outlive a session: local storage in the browser,
+// files under the user's config directory on native.
+
+#[derive(Debug)]
+pub enum StorageError {
+    /// No config directory or no local storage to write to
+    Unavailable,
+    #[cfg(not(target_arch = "wasm32"))]
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::Unavailable => write!(f, "no storage available"),
+            #[cfg(not(target_arch = "wasm32"))]
+            StorageError::Io(error) => write!(f, "couldn't write file: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Binary data as base64 text, for storing alongside RON.
+pub fn encode_bytes(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate()
+            .fold(0u32, |group, (index, &byte)| group | ((byte as u32) << (16 - 8 * index)));
+        for index in 0..4 {
+            if index <= chunk.len() {
+                text.push(BASE64[((group >> (18 - 6 * index)) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+/// Reverses `encode_bytes`. `None` if the text isn't valid base64.
+pub fn decode_bytes(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=').as_bytes();
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut group = 0u32;
+        for (index, &symbol) in chunk.iter().enumerate() {
+            let value = BASE64.iter().position(|&c| c == symbol)? as u32;
+            group |= value << (18 - 6 * index);
+        }
+        for index in 0..chunk.len() - 1 {
+            bytes.push((group >> (16 - 8 * index)) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn config_dir() -> Option<std::path::PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .or_else(|| std::env::var_os("APPDATA"))
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".config")))?;
+    Some(base.join("wasm-worms"))
+}
+
+/// Where `key` is kept on native builds.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn path(key: &str) -> Option<std::path::PathBuf> {
+    config_dir().map(|dir| dir.join(format!("{}.ron", key)))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(key: &str) -> Option<String> {
+    std::fs::read_to_string(path(key)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(key: &str, contents: &str) -> Result<(), StorageError> {
+    let path = path(key).ok_or(StorageError::Unavailable)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(StorageError::Io)?;
+    }
+    std::fs::write(path, contents).map_err(StorageError::Io)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remove(key: &str) {
+    if let Some(path) = path(key) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load(key: &str) -> Option<String> {
+    local_storage()?.get_item(&format!("wasm-worms.{}", key)).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(key: &str, contents: &str) -> Result<(), StorageError> {
+    local_storage()
+        .ok_or(StorageError::Unavailable)?
+        .set_item(&format!("wasm-worms.{}", key), contents)
+        .map_err(|_| StorageError::Unavailable)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn remove(key: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.remove_item(&format!("wasm-worms.{}", key));
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use crate::game::replay::simulation_running;
+use crate::game::hotseat::local_input_allowed;
+use crate::game::weapons::{WeaponInventory, WeaponCategory, WeaponType, spawn_projectile};
+use crate::game::game_state::{GameState, TurnFlow};
+use crate::game::terrain::TerrainMap;
+use crate::game::camera::WorldCursor;
+use crate::game::events::{IssueWormCommands, WormCommand};
+
+pub struct StrikePlugin;
+
+impl Plugin for StrikePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(StrikeTargeting::default())
+            .add_systems(Update, (
+                handle_strike_targeting.after(IssueWormCommands),
+                track_strike_cursor.run_if(local_input_allowed),
+                update_strike_marker,
+            ).chain().run_if(simulation_running));
+    }
+}
+
+/// Input state for weapons that are called in on a map point instead of fired from the worm.
+#[derive(Resource)]
+pub struct StrikeTargeting {
+    pub is_targeting: bool,
+    pub cursor_position: Option<Vec2>,
+    pub direction: f32, // -1.0 = bombs travel left, 1.0 = bombs travel right
+}
+
+impl Default for StrikeTargeting {
+    fn default() -> Self {
+        Self {
+            is_targeting: false,
+            cursor_position: None,
+            direction: 1.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct StrikeMarker;
+
+// Bombs are released this far above the top edge of the terrain
+const DROP_HEIGHT_ABOVE_MAP: f32 = 100.0;
+const GRAVITY: f32 = 980.0;
+
+fn handle_strike_targeting(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut worm_commands: EventReader<WormCommand>,
+    mut targeting: ResMut<StrikeTargeting>,
+    game_state: Res<GameState>,
+    mut turn: TurnFlow,
+    weapon_inventory: Res<WeaponInventory>,
+    terrain: Res<TerrainMap>,
+) {
+    let current_weapon = match weapon_inventory.weapons.get(weapon_inventory.current_weapon) {
+        Some(weapon) if weapon.category() == WeaponCategory::TargetedStrike => weapon.clone(),
+        _ => {
+            targeting.is_targeting = false;
+            worm_commands.clear();
+            return;
+        }
+    };
+
+    if !turn.can_player_act() {
+        targeting.is_targeting = false;
+        worm_commands.clear();
+        return;
+    }
+
+    for command in worm_commands.read() {
+        match *command {
+            // Toggle targeting mode
+            WormCommand::ToggleAim => {
+                if !targeting.is_targeting {
+                    targeting.is_targeting = true;
+                    turn.start_aiming();
+                } else {
+                    targeting.is_targeting = false;
+                    turn.cancel_aiming(); // Return to turn mode
+                }
+            }
+            // Choose which way the bombers fly
+            WormCommand::Move(direction) if targeting.is_targeting && direction != 0.0 => {
+                targeting.direction = direction.signum();
+            }
+            WormCommand::Target(target) if targeting.is_targeting => {
+                launch_strike(
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    &terrain,
+                    current_weapon.clone(),
+                    Some(game_state.current_player),
+                    target,
+                    targeting.direction,
+                );
+
+                // Update game state
+                turn.projectile_launched();
+
+                targeting.is_targeting = false;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The marker follows the mouse of whoever is playing at this screen.
+fn track_strike_cursor(mut targeting: ResMut<StrikeTargeting>, cursor: WorldCursor) {
+    targeting.cursor_position = cursor.position();
+}
+
+/// Drops the bombs of a targeted strike from above the map so that they land around `target`.
+pub fn launch_strike(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    terrain: &TerrainMap,
+    weapon_type: WeaponType,
+    owner_team: Option<u32>,
+    target: Vec2,
+    direction: f32,
+) {
+    let Some(strike) = weapon_type.strike_stats() else {
+        return;
+    };
+    let stats = weapon_type.get_stats();
+
+    let drop_y = terrain.height as f32 / 2.0 + DROP_HEIGHT_ABOVE_MAP;
+    let fall_height = (drop_y - target.y).max(0.0);
+    let fall_time = (2.0 * fall_height / (GRAVITY * stats.gravity_scale)).sqrt();
+
+    // Release each bomb early enough that its horizontal drift carries it over the target
+    let velocity = Vec2::new(direction * strike.horizontal_speed, 0.0);
+    let release_x = target.x - velocity.x * fall_time;
+
+    for i in 0..strike.bomb_count {
+        let offset = (i as f32 - (strike.bomb_count as f32 - 1.0) / 2.0) * strike.bomb_spacing;
+
+        spawn_projectile(
+            commands,
+            meshes,
+            materials,
+            weapon_type.clone(),
+            owner_team,
+            Vec3::new(release_x + offset, drop_y, 0.1),
+            velocity,
+        );
+    }
+}
+
+fn update_strike_marker(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    targeting: Res<StrikeTargeting>,
+    marker_query: Query<Entity, With<StrikeMarker>>,
+) {
+    // Clean up existing markers
+    for entity in marker_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if !targeting.is_targeting {
+        return;
+    }
+
+    let Some(target) = targeting.cursor_position else {
+        return;
+    };
+
+    // Target crosshair
+    commands.spawn((
+        Mesh2d(meshes.add(bevy::math::primitives::Circle::new(10.0))),
+        MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgba(1.0, 0.2, 0.2, 0.7)))),
+        Transform::from_translation(target.extend(0.5)),
+        StrikeMarker,
+    ));
+
+    // Direction indicator showing which way the bombers fly
+    commands.spawn((
+        Mesh2d(meshes.add(bevy::math::primitives::Rectangle::new(30.0, 4.0))),
+        MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgba(1.0, 1.0, 1.0, 0.8)))),
+        Transform::from_translation(Vec3::new(target.x + targeting.direction * 25.0, target.y + 20.0, 0.5)),
+        StrikeMarker,
+    ));
+}
@@ -1,14 +1,42 @@
 use bevy::prelude::*;
 use std::collections::HashMap;
+use crate::game::replay::simulation_running;
+use crate::game::game_state::GamePhase;
 
 pub struct TerrainPlugin;
 
 impl Plugin for TerrainPlugin {
     fn build(&self, app: &mut App) {
         app
-            .insert_resource(TerrainMap::new(2048, 1024)) // Much larger terrain
-            .add_systems(Startup, setup_terrain)
-            .add_systems(Update, update_terrain_mesh);
+            // Each match puts in its own map from its seed
+            .insert_resource(TerrainMap::new(MAP_WIDTH, MAP_HEIGHT, &mut fastrand::Rng::with_seed(0)))
+            .init_resource::<MapImages>()
+            .add_systems(Startup, load_map_images)
+            .add_systems(Update, update_terrain_mesh.run_if(simulation_running))
+            // Burning napalm carves a little every fraction of a second, so the edit
+            // log only goes back to the start of the turn
+            .add_systems(OnEnter(GamePhase::TurnTransition), rebase_terrain);
+    }
+}
+
+fn rebase_terrain(mut terrain: ResMut<TerrainMap>) {
+    terrain.rebase();
+}
+
+// Size of generated maps
+pub const MAP_WIDTH: usize = 2048;
+pub const MAP_HEIGHT: usize = 1024;
+
+/// Hand-drawn maps that can be picked in match setup, relative to `assets/`.
+pub const MAP_IMAGES: &[&str] = &["maps/islands.png"];
+
+/// Handles to the map images, loaded up front so they are ready by the time a match starts.
+#[derive(Resource, Default)]
+pub struct MapImages(pub HashMap<String, Handle<Image>>);
+
+fn load_map_images(asset_server: Res<AssetServer>, mut map_images: ResMut<MapImages>) {
+    for path in MAP_IMAGES {
+        map_images.0.insert(path.to_string(), asset_server.load(*path));
     }
 }
 
@@ -17,12 +45,25 @@ pub struct TerrainMap {
     pub width: usize,
     pub height: usize,
     pub pixels: Vec<bool>, // true = solid, false = empty
+    pub indestructible: Vec<bool>, // true = survives explosions (girders)
     pub dirty_chunks: HashMap<(i32, i32), bool>,
     pub chunk_size: usize,
+    /// Craters and girders since the map was built or last rebased, in order; see `encode_delta`
+    pub edits: Vec<TerrainEdit>,
+    /// Checksum of the map before those edits
+    pub base_checksum: u32,
+}
+
+/// One change to the terrain, enough to replay it on another copy of the same map.
+/// Coordinates are in terrain space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TerrainEdit {
+    Carve { x: f32, y: f32, radius: f32 },
+    Girder { x: f32, y: f32, length: f32, thickness: f32, angle: f32 },
 }
 
 impl TerrainMap {
-    pub fn new(width: usize, height: usize) -> Self {
+    pub fn new(width: usize, height: usize, rng: &mut fastrand::Rng) -> Self {
         let mut pixels = vec![false; width * height];
         
         // Generate varied terrain with multiple layers of hills
@@ -42,7 +83,7 @@ impl TerrainMap {
             let small_hills = ((x_norm * std::f32::consts::PI * 20.0).sin() * 15.0) as i32;
             
             // Add some randomness
-            let random_offset = (fastrand::f32() * 20.0 - 10.0) as i32;
+            let random_offset = (rng.f32() * 20.0 - 10.0) as i32;
             
             let terrain_height = (base_height + large_hills + medium_hills + small_hills + random_offset)
                 .max(height as i32 / 10) // Minimum height
@@ -54,12 +95,50 @@ impl TerrainMap {
             }
         }
         
+        Self::build(width, height, pixels, vec![false; width * height])
+    }
+    
+    /// Builds a map from an RGBA image. Opaque pixels are ground; the image's top row
+    /// is the top of the map.
+    pub fn from_image(image: &Image) -> Option<Self> {
+        let width = image.width() as usize;
+        let height = image.height() as usize;
+        let data = image.data.as_ref()?;
+        if data.len() < width * height * 4 {
+            return None;
+        }
+
+        let mut pixels = vec![false; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                let alpha = data[((height - 1 - y) * width + x) * 4 + 3];
+                pixels[y * width + x] = alpha > 127;
+            }
+        }
+
+        Some(Self::build(width, height, pixels, vec![false; width * height]))
+    }
+    
+    /// Rebuilds a map from pixels saved earlier. `None` if the sizes don't add up.
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<bool>, indestructible: Vec<bool>) -> Option<Self> {
+        if pixels.len() != width * height || indestructible.len() != width * height {
+            return None;
+        }
+
+        Some(Self::build(width, height, pixels, indestructible))
+    }
+
+    fn build(width: usize, height: usize, pixels: Vec<bool>, indestructible: Vec<bool>) -> Self {
+        let base_checksum = crate::game::terrain_codec::checksum(&pixels, &indestructible);
         Self {
             width,
             height,
             pixels,
+            indestructible,
             dirty_chunks: HashMap::new(),
             chunk_size: 64,
+            edits: Vec::new(),
+            base_checksum,
         }
     }
     
@@ -70,7 +149,17 @@ impl TerrainMap {
         self.pixels[y as usize * self.width + x as usize]
     }
     
+    /// Height of the topmost solid pixel in column `x`, if the column has any ground.
+    pub fn surface_height(&self, x: usize) -> Option<usize> {
+        if x >= self.width {
+            return None;
+        }
+        (0..self.height).rev().find(|&y| self.pixels[y * self.width + x])
+    }
+    
     pub fn destroy_circle(&mut self, center_x: f32, center_y: f32, radius: f32) {
+        self.edits.push(TerrainEdit::Carve { x: center_x, y: center_y, radius });
+
         let min_x = ((center_x - radius) as i32).max(0);
         let max_x = ((center_x + radius) as i32).min(self.width as i32 - 1);
         let min_y = ((center_y - radius) as i32).max(0);
@@ -84,7 +173,7 @@ impl TerrainMap {
                 
                 if distance <= radius {
                     let index = y as usize * self.width + x as usize;
-                    if self.pixels[index] {
+                    if self.pixels[index] && !self.indestructible[index] {
                         self.pixels[index] = false;
                         
                         // Mark chunk as dirty
@@ -97,6 +186,57 @@ impl TerrainMap {
         }
     }
     
+    /// Fills a rotated rectangle with indestructible solid pixels.
+    /// `angle` is in radians, coordinates are in terrain space.
+    pub fn place_girder(&mut self, center_x: f32, center_y: f32, length: f32, thickness: f32, angle: f32) {
+        self.edits.push(TerrainEdit::Girder { x: center_x, y: center_y, length, thickness, angle });
+
+        let reach = (length.max(thickness) / 2.0).ceil();
+        let min_x = ((center_x - reach) as i32).max(0);
+        let max_x = ((center_x + reach) as i32).min(self.width as i32 - 1);
+        let min_y = ((center_y - reach) as i32).max(0);
+        let max_y = ((center_y + reach) as i32).min(self.height as i32 - 1);
+        
+        let (sin_a, cos_a) = angle.sin_cos();
+        
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                // Rotate into the girder's local space
+                let dx = x as f32 - center_x;
+                let dy = y as f32 - center_y;
+                let local_x = dx * cos_a + dy * sin_a;
+                let local_y = -dx * sin_a + dy * cos_a;
+                
+                if local_x.abs() <= length / 2.0 && local_y.abs() <= thickness / 2.0 {
+                    let index = y as usize * self.width + x as usize;
+                    self.pixels[index] = true;
+                    self.indestructible[index] = true;
+                    
+                    let chunk_x = x / self.chunk_size as i32;
+                    let chunk_y = y / self.chunk_size as i32;
+                    self.dirty_chunks.insert((chunk_x, chunk_y), true);
+                }
+            }
+        }
+    }
+    
+    /// Takes the map as it is now as the base for deltas, dropping the edits so far.
+    pub fn rebase(&mut self) {
+        self.edits.clear();
+        self.base_checksum = crate::game::terrain_codec::checksum(&self.pixels, &self.indestructible);
+    }
+
+    /// Replays an edit recorded on another copy of this map.
+    #[allow(dead_code)]
+    pub fn apply_edit(&mut self, edit: TerrainEdit) {
+        match edit {
+            TerrainEdit::Carve { x, y, radius } => self.destroy_circle(x, y, radius),
+            TerrainEdit::Girder { x, y, length, thickness, angle } => {
+                self.place_girder(x, y, length, thickness, angle)
+            }
+        }
+    }
+    
     pub fn check_collision(&self, x: f32, y: f32, radius: f32) -> bool {
         let min_x = ((x - radius) as i32).max(0);
         let max_x = ((x + radius) as i32).min(self.width as i32 - 1);
@@ -123,7 +263,7 @@ impl TerrainMap {
 #[derive(Component)]
 pub struct TerrainRenderer;
 
-fn setup_terrain(
+pub fn setup_terrain(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
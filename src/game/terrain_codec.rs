@@ -0,0 +1,432 @@
+// Compact binary form of `TerrainMap`, so saves, replays and network sync can ship
+// terrain cheaply. A bare 2048x1024 map is 2MB of bools; this is usually a few KB.
+//
+// Every blob starts with the same header, numbers little endian throughout:
+//   [u8; 4]  magic, "WWTM"
+//   u8       format version
+//   u8       kind, 0 = full map, 1 = delta
+//   u32      width
+//   u32      height
+//
+// A full map follows with the solid and indestructible layers. Each is bit-packed,
+// eight pixels to a byte in the map's row order, then run-length compressed
+// (PackBits) and prefixed with its compressed length as a u32.
+//
+// A delta follows with the checksum of the map it was made from, the number of
+// edits, then the edits in order: a tag byte (0 = carve, 1 = girder) and the edit's
+// parameters as f32s. Applying it replays the same `destroy_circle` and
+// `place_girder` calls on another copy of that map. The map is rebased when each
+// turn ends, so a delta holds at most one turn of edits. Nothing sends deltas until
+// network sync lands, so that half of the format is only exercised by the tests.
+
+use crate::game::terrain::{TerrainMap, TerrainEdit};
+
+const MAGIC: &[u8; 4] = b"WWTM";
+
+// Bumped whenever the layout changes; older data is refused rather than misread
+pub const FORMAT_VERSION: u8 = 1;
+
+const KIND_FULL: u8 = 0;
+#[allow(dead_code)]
+const KIND_DELTA: u8 = 1;
+
+#[allow(dead_code)]
+const EDIT_CARVE: u8 = 0;
+#[allow(dead_code)]
+const EDIT_GIRDER: u8 = 1;
+
+#[derive(Debug, PartialEq)]
+pub enum TerrainCodecError {
+    /// Not terrain data at all
+    BadMagic,
+    UnsupportedVersion(u8),
+    /// A delta where a full map was expected, or the other way round
+    WrongKind(u8),
+    Truncated,
+    /// The pixel data doesn't fit the map's size
+    SizeMismatch,
+    /// The delta was made from a different map than the one it is applied to
+    #[allow(dead_code)]
+    BaseMismatch,
+    #[allow(dead_code)]
+    UnknownEdit(u8),
+}
+
+impl std::fmt::Display for TerrainCodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TerrainCodecError::BadMagic => write!(f, "not terrain data"),
+            TerrainCodecError::UnsupportedVersion(version) => write!(f, "unsupported terrain format version {}", version),
+            TerrainCodecError::WrongKind(kind) => write!(f, "unexpected kind of terrain data {}", kind),
+            TerrainCodecError::Truncated => write!(f, "terrain data is cut short"),
+            TerrainCodecError::SizeMismatch => write!(f, "terrain data doesn't match the map size"),
+            TerrainCodecError::BaseMismatch => write!(f, "terrain delta is for a different map"),
+            TerrainCodecError::UnknownEdit(tag) => write!(f, "unknown terrain edit {}", tag),
+        }
+    }
+}
+
+impl std::error::Error for TerrainCodecError {}
+
+impl TerrainMap {
+    /// The whole map in the compact format.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = header(KIND_FULL, self.width, self.height);
+        for layer in [&self.pixels, &self.indestructible] {
+            let compressed = compress(&pack_bits(layer));
+            bytes.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&compressed);
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, TerrainCodecError> {
+        let mut reader = Reader::new(bytes);
+        let (kind, width, height) = reader.header()?;
+        if kind != KIND_FULL {
+            return Err(TerrainCodecError::WrongKind(kind));
+        }
+
+        let len = width.checked_mul(height).ok_or(TerrainCodecError::SizeMismatch)?;
+        let pixels = reader.layer(len)?;
+        let indestructible = reader.layer(len)?;
+        TerrainMap::from_pixels(width, height, pixels, indestructible)
+            .ok_or(TerrainCodecError::SizeMismatch)
+    }
+}
+
+#[allow(dead_code)]
+impl TerrainMap {
+    /// Every edit made since the map was built or rebased, to be applied to another copy of it.
+    pub fn encode_delta(&self) -> Vec<u8> {
+        let mut bytes = header(KIND_DELTA, self.width, self.height);
+        bytes.extend_from_slice(&self.base_checksum.to_le_bytes());
+        bytes.extend_from_slice(&(self.edits.len() as u32).to_le_bytes());
+
+        for edit in self.edits.iter() {
+            match *edit {
+                TerrainEdit::Carve { x, y, radius } => {
+                    bytes.push(EDIT_CARVE);
+                    push_floats(&mut bytes, &[x, y, radius]);
+                }
+                TerrainEdit::Girder { x, y, length, thickness, angle } => {
+                    bytes.push(EDIT_GIRDER);
+                    push_floats(&mut bytes, &[x, y, length, thickness, angle]);
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Replays a delta made by `encode_delta`. The map has to be in the state the
+    /// delta was made from; on any error it is left untouched.
+    pub fn apply_delta(&mut self, bytes: &[u8]) -> Result<(), TerrainCodecError> {
+        let mut reader = Reader::new(bytes);
+        let (kind, width, height) = reader.header()?;
+        if kind != KIND_DELTA {
+            return Err(TerrainCodecError::WrongKind(kind));
+        }
+        if width != self.width || height != self.height {
+            return Err(TerrainCodecError::SizeMismatch);
+        }
+        if reader.u32()? != checksum(&self.pixels, &self.indestructible) {
+            return Err(TerrainCodecError::BaseMismatch);
+        }
+
+        let count = reader.u32()?;
+        let mut edits = Vec::new();
+        for _ in 0..count {
+            let edit = match reader.u8()? {
+                EDIT_CARVE => TerrainEdit::Carve {
+                    x: reader.f32()?,
+                    y: reader.f32()?,
+                    radius: reader.f32()?,
+                },
+                EDIT_GIRDER => TerrainEdit::Girder {
+                    x: reader.f32()?,
+                    y: reader.f32()?,
+                    length: reader.f32()?,
+                    thickness: reader.f32()?,
+                    angle: reader.f32()?,
+                },
+                tag => return Err(TerrainCodecError::UnknownEdit(tag)),
+            };
+            edits.push(edit);
+        }
+
+        for edit in edits {
+            self.apply_edit(edit);
+        }
+        Ok(())
+    }
+}
+
+/// FNV-1a over both layers, to tell whether a delta fits a map.
+pub fn checksum(pixels: &[bool], indestructible: &[bool]) -> u32 {
+    pack_bits(pixels).into_iter()
+        .chain(pack_bits(indestructible))
+        .fold(0x811c_9dc5, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193))
+}
+
+fn header(kind: u8, width: usize, height: usize) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(FORMAT_VERSION);
+    bytes.push(kind);
+    bytes.extend_from_slice(&(width as u32).to_le_bytes());
+    bytes.extend_from_slice(&(height as u32).to_le_bytes());
+    bytes
+}
+
+#[allow(dead_code)]
+fn push_floats(bytes: &mut Vec<u8>, values: &[f32]) {
+    for value in values {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn pack_bits(cells: &[bool]) -> Vec<u8> {
+    let mut bytes = vec![0u8; cells.len().div_ceil(8)];
+    for (index, &cell) in cells.iter().enumerate() {
+        if cell {
+            bytes[index / 8] |= 1 << (index % 8);
+        }
+    }
+    bytes
+}
+
+fn unpack_bits(bytes: &[u8], len: usize) -> Option<Vec<bool>> {
+    if bytes.len() != len.div_ceil(8) {
+        return None;
+    }
+    Some((0..len).map(|index| bytes[index / 8] & (1 << (index % 8)) != 0).collect())
+}
+
+/// PackBits: a control byte of 0-127 is followed by that many plus one literal bytes,
+/// 129-255 by one byte repeated 257 minus that many times.
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut index = 0;
+
+    while index < data.len() {
+        let mut run = 1;
+        while index + run < data.len() && run < 128 && data[index + run] == data[index] {
+            run += 1;
+        }
+
+        if run > 1 {
+            out.push((257 - run) as u8);
+            out.push(data[index]);
+            index += run;
+            continue;
+        }
+
+        // Literals up to where the next run starts
+        let start = index;
+        while index < data.len()
+            && index - start < 128
+            && !(index + 1 < data.len() && data[index] == data[index + 1])
+        {
+            index += 1;
+        }
+        out.push((index - start - 1) as u8);
+        out.extend_from_slice(&data[start..index]);
+    }
+
+    out
+}
+
+/// Reverses `compress`. `None` unless it comes out at exactly `len` bytes.
+fn decompress(data: &[u8], len: usize) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut index = 0;
+
+    while index < data.len() {
+        let control = data[index] as usize;
+        index += 1;
+
+        match control {
+            0..=127 => {
+                let literal = data.get(index..index + control + 1)?;
+                out.extend_from_slice(literal);
+                index += control + 1;
+            }
+            128 => {}
+            _ => {
+                let byte = *data.get(index)?;
+                out.extend(std::iter::repeat_n(byte, 257 - control));
+                index += 1;
+            }
+        }
+
+        if out.len() > len {
+            return None;
+        }
+    }
+
+    (out.len() == len).then_some(out)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], TerrainCodecError> {
+        let slice = self.position.checked_add(count)
+            .and_then(|end| self.bytes.get(self.position..end))
+            .ok_or(TerrainCodecError::Truncated)?;
+        self.position += count;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, TerrainCodecError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, TerrainCodecError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    #[allow(dead_code)]
+    fn f32(&mut self) -> Result<f32, TerrainCodecError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    /// Kind, width and height.
+    fn header(&mut self) -> Result<(u8, usize, usize), TerrainCodecError> {
+        if self.take(MAGIC.len())? != MAGIC {
+            return Err(TerrainCodecError::BadMagic);
+        }
+        let version = self.u8()?;
+        if version != FORMAT_VERSION {
+            return Err(TerrainCodecError::UnsupportedVersion(version));
+        }
+        let kind = self.u8()?;
+        let width = self.u32()? as usize;
+        let height = self.u32()? as usize;
+        Ok((kind, width, height))
+    }
+
+    fn layer(&mut self, len: usize) -> Result<Vec<bool>, TerrainCodecError> {
+        let compressed_len = self.u32()? as usize;
+        let compressed = self.take(compressed_len)?;
+        let packed = decompress(compressed, len.div_ceil(8)).ok_or(TerrainCodecError::SizeMismatch)?;
+        unpack_bits(&packed, len).ok_or(TerrainCodecError::SizeMismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generated_map() -> TerrainMap {
+        TerrainMap::new(256, 128, &mut fastrand::Rng::with_seed(7))
+    }
+
+    fn same_pixels(a: &TerrainMap, b: &TerrainMap) -> bool {
+        a.width == b.width
+            && a.height == b.height
+            && a.pixels == b.pixels
+            && a.indestructible == b.indestructible
+    }
+
+    #[test]
+    fn generated_map_round_trips() {
+        let mut terrain = generated_map();
+        terrain.place_girder(128.0, 80.0, 60.0, 8.0, 0.3);
+
+        let decoded = TerrainMap::decode(&terrain.encode()).unwrap();
+        assert!(same_pixels(&decoded, &terrain));
+    }
+
+    #[test]
+    fn delta_replays_onto_its_base() {
+        let mut edited = generated_map();
+        edited.destroy_circle(40.0, 10.0, 8.0);
+        edited.place_girder(128.0, 60.0, 60.0, 8.0, 0.8);
+        edited.destroy_circle(128.0, 60.0, 25.0);
+
+        let mut copy = generated_map();
+        copy.apply_delta(&edited.encode_delta()).unwrap();
+        assert!(same_pixels(&copy, &edited));
+        assert_eq!(copy.edits, edited.edits);
+    }
+
+    #[test]
+    fn rebase_starts_the_next_delta() {
+        let mut terrain = generated_map();
+        terrain.destroy_circle(40.0, 10.0, 8.0);
+        let mut copy = generated_map();
+        copy.apply_delta(&terrain.encode_delta()).unwrap();
+
+        terrain.rebase();
+        copy.rebase();
+        assert!(terrain.edits.is_empty());
+
+        terrain.destroy_circle(200.0, 5.0, 4.0);
+        copy.apply_delta(&terrain.encode_delta()).unwrap();
+        assert!(same_pixels(&copy, &terrain));
+        assert_eq!(copy.edits, terrain.edits);
+    }
+
+    #[test]
+    fn truncated_or_corrupt_data_is_an_error() {
+        let bytes = generated_map().encode();
+        for len in 0..bytes.len() {
+            assert!(TerrainMap::decode(&bytes[..len]).is_err(), "decoded {} of {} bytes", len, bytes.len());
+        }
+
+        // Everything after the header scrambled
+        let mut corrupt = bytes.clone();
+        for byte in corrupt.iter_mut().skip(14) {
+            *byte = byte.wrapping_mul(31).wrapping_add(7);
+        }
+        assert!(TerrainMap::decode(&corrupt).is_err());
+
+        let mut edited = generated_map();
+        edited.destroy_circle(40.0, 10.0, 8.0);
+        let delta = edited.encode_delta();
+        for len in 0..delta.len() {
+            let mut terrain = generated_map();
+            assert!(terrain.apply_delta(&delta[..len]).is_err());
+            assert!(same_pixels(&terrain, &generated_map()), "a failed delta changed the map");
+        }
+    }
+
+    #[test]
+    fn delta_refuses_a_different_base() {
+        let mut edited = generated_map();
+        edited.destroy_circle(40.0, 10.0, 8.0);
+        let delta = edited.encode_delta();
+
+        let mut moved_on = generated_map();
+        moved_on.destroy_circle(200.0, 5.0, 4.0);
+        let before = moved_on.pixels.clone();
+
+        assert_eq!(moved_on.apply_delta(&delta), Err(TerrainCodecError::BaseMismatch));
+        assert_eq!(moved_on.pixels, before);
+    }
+
+    #[test]
+    fn other_versions_are_refused() {
+        let mut bytes = generated_map().encode();
+        bytes[MAGIC.len()] = FORMAT_VERSION + 1;
+        assert_eq!(
+            TerrainMap::decode(&bytes).err(),
+            Some(TerrainCodecError::UnsupportedVersion(FORMAT_VERSION + 1)),
+        );
+
+        let mut delta = generated_map().encode_delta();
+        delta[MAGIC.len()] = FORMAT_VERSION + 1;
+        assert_eq!(
+            generated_map().apply_delta(&delta),
+            Err(TerrainCodecError::UnsupportedVersion(FORMAT_VERSION + 1)),
+        );
+    }
+}
@@ -0,0 +1,419 @@
+use bevy::prelude::*;
+use crate::game::physics::{RigidBody, Collider};
+use crate::game::weapons::{WeaponInventory, WeaponCategory, WeaponType};
+use crate::game::worm::{Worm, ActiveWorm};
+use crate::game::replay::simulation_running;
+use crate::game::game_state::{GameState, GamePhase, TurnFlow};
+use crate::game::terrain::TerrainMap;
+use crate::game::camera::WorldCursor;
+use crate::game::events::{IssueWormCommands, WormCommand};
+
+pub struct ToolPlugin;
+
+impl Plugin for ToolPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(ToolState::default())
+            .add_systems(Update, (
+                handle_tool_input.after(IssueWormCommands),
+                ninja_rope_physics.after(IssueWormCommands),
+                jetpack_flight.after(IssueWormCommands),
+                update_rope_visual,
+                update_girder_preview,
+            ).run_if(simulation_running))
+            // The turn timer can run out mid-swing
+            .add_systems(OnExit(GamePhase::UsingTool), release_tools)
+            .add_systems(OnEnter(GamePhase::TurnTransition), reset_tool_uses);
+    }
+}
+
+/// Per-turn bookkeeping for tools. Girders and teleports are limited so they
+/// can't be chained endlessly within one turn.
+#[derive(Resource)]
+pub struct ToolState {
+    pub girder_angle: f32, // degrees
+    pub girders_per_turn: u32,
+    pub girders_placed: u32,
+    pub teleports_per_turn: u32,
+    pub teleports_used: u32,
+}
+
+impl Default for ToolState {
+    fn default() -> Self {
+        Self {
+            girder_angle: 0.0,
+            girders_per_turn: 1,
+            girders_placed: 0,
+            teleports_per_turn: 1,
+            teleports_used: 0,
+        }
+    }
+}
+
+/// Worm is hanging from a ninja rope anchored in the terrain.
+#[derive(Component)]
+pub struct RopeAttachment {
+    pub anchor: Vec2,
+    pub length: f32,
+}
+
+/// Worm is flying with a jetpack until it runs out of fuel or lands.
+#[derive(Component)]
+pub struct JetpackFlight {
+    pub fuel: f32,
+    pub max_fuel: f32,
+}
+
+#[derive(Component)]
+pub struct RopeVisual;
+
+#[derive(Component)]
+pub struct GirderPreview;
+
+/// Placed girders are drawn on top of the terrain so they stand out.
+#[derive(Component)]
+pub struct GirderVisual;
+
+const ROPE_MAX_LENGTH: f32 = 400.0;
+const ROPE_MIN_LENGTH: f32 = 20.0;
+const ROPE_CLIMB_SPEED: f32 = 150.0;
+const ROPE_SWING_FORCE: f32 = 600.0;
+const JETPACK_FUEL: f32 = 5.0; // seconds of thrust
+const JETPACK_THRUST: f32 = 1400.0;
+const JETPACK_STRAFE: f32 = 300.0;
+const GIRDER_LENGTH: f32 = 120.0;
+const GIRDER_THICKNESS: f32 = 10.0;
+
+fn handle_tool_input(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut worm_commands: EventReader<WormCommand>,
+    game_state: Res<GameState>,
+    mut turn: TurnFlow,
+    mut tool_state: ResMut<ToolState>,
+    mut terrain: ResMut<TerrainMap>,
+    weapon_inventory: Res<WeaponInventory>,
+    mut worm_query: Query<(Entity, &mut Transform, &mut RigidBody, &Collider, &Worm), With<ActiveWorm>>,
+) {
+    let mut target = None;
+    let mut take_off = false;
+    let mut girder_turn = 0.0;
+    for command in worm_commands.read() {
+        match *command {
+            WormCommand::Target(point) => target = Some(point),
+            WormCommand::TakeOff => take_off = true,
+            WormCommand::RotateGirder(degrees) => girder_turn += degrees,
+            _ => {}
+        }
+    }
+
+    if !turn.can_player_act() {
+        return;
+    }
+
+    let current_weapon = match weapon_inventory.weapons.get(weapon_inventory.current_weapon) {
+        Some(weapon) if weapon.category() == WeaponCategory::Tool => weapon.clone(),
+        _ => return,
+    };
+
+    let Some((entity, mut transform, mut body, collider, _)) = worm_query.iter_mut()
+        .find(|(_, _, _, _, worm)| worm.team == game_state.current_player && worm.health > 0.0) else {
+        return;
+    };
+
+    let worm_position = transform.translation.truncate();
+
+    match current_weapon {
+        WeaponType::NinjaRope => {
+            let Some(target) = target else {
+                return;
+            };
+            if let Some(anchor) = find_rope_anchor(&terrain, worm_position, target) {
+                commands.entity(entity).insert(RopeAttachment {
+                    anchor,
+                    length: worm_position.distance(anchor).max(ROPE_MIN_LENGTH),
+                });
+                turn.start_tool_use();
+            }
+        }
+        WeaponType::Jetpack if take_off => {
+            commands.entity(entity).insert(JetpackFlight {
+                fuel: JETPACK_FUEL,
+                max_fuel: JETPACK_FUEL,
+            });
+            body.velocity.y = body.velocity.y.max(100.0); // Lift off
+            turn.start_tool_use();
+        }
+        WeaponType::Girder => {
+            // A girder looks the same turned half way round
+            tool_state.girder_angle = (tool_state.girder_angle + girder_turn).rem_euclid(180.0);
+
+            let Some(target) = target else {
+                return;
+            };
+            if tool_state.girders_placed >= tool_state.girders_per_turn {
+                return;
+            }
+
+            let angle = tool_state.girder_angle.to_radians();
+            let terrain_x = target.x + terrain.width as f32 / 2.0;
+            let terrain_y = target.y + terrain.height as f32 / 2.0;
+            terrain.place_girder(
+                terrain_x,
+                terrain_y,
+                GIRDER_LENGTH,
+                GIRDER_THICKNESS,
+                angle,
+            );
+            tool_state.girders_placed += 1;
+
+            spawn_girder_visual(&mut commands, &mut meshes, &mut materials, target, angle);
+        }
+        WeaponType::Teleport => {
+            let Some(target) = target else {
+                return;
+            };
+            if tool_state.teleports_used >= tool_state.teleports_per_turn {
+                return;
+            }
+
+            let terrain_x = target.x + terrain.width as f32 / 2.0;
+            let terrain_y = target.y + terrain.height as f32 / 2.0;
+            let inside_map = terrain_x >= 0.0 && terrain_x < terrain.width as f32
+                && terrain_y >= 0.0 && terrain_y < terrain.height as f32;
+
+            // Can't teleport into solid ground
+            if inside_map && !terrain.check_collision(terrain_x, terrain_y, collider.radius) {
+                transform.translation.x = target.x;
+                transform.translation.y = target.y;
+                body.velocity = Vec2::ZERO;
+                tool_state.teleports_used += 1;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Walks from `from` towards `toward` and returns the first solid terrain point within rope range.
+/// Draws a girder already in the terrain at `position`, turned by `angle` radians.
+pub fn spawn_girder_visual(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    position: Vec2,
+    angle: f32,
+) -> Entity {
+    commands.spawn((
+        Mesh2d(meshes.add(bevy::math::primitives::Rectangle::new(GIRDER_LENGTH, GIRDER_THICKNESS))),
+        MeshMaterial2d(materials.add(ColorMaterial::from(WeaponType::Girder.get_color()))),
+        Transform::from_translation(position.extend(0.05))
+            .with_rotation(Quat::from_rotation_z(angle)),
+        GirderVisual,
+    )).id()
+}
+
+fn find_rope_anchor(terrain: &TerrainMap, from: Vec2, toward: Vec2) -> Option<Vec2> {
+    let direction = (toward - from).normalize_or_zero();
+    if direction == Vec2::ZERO {
+        return None;
+    }
+
+    let half_size = Vec2::new(terrain.width as f32 / 2.0, terrain.height as f32 / 2.0);
+    let mut distance = 0.0;
+    while distance <= ROPE_MAX_LENGTH {
+        let point = from + direction * distance;
+        let terrain_point = point + half_size;
+        if terrain.is_solid(terrain_point.x as i32, terrain_point.y as i32) {
+            return Some(point);
+        }
+        distance += 2.0;
+    }
+    None
+}
+
+/// This frame's commands for a worm on a rope or jetpack.
+#[derive(Default)]
+struct ToolControls {
+    let_go: bool,
+    climb: f32,
+    sideways: f32,
+}
+
+impl ToolControls {
+    fn read(worm_commands: &mut EventReader<WormCommand>) -> Self {
+        let mut controls = Self::default();
+        for command in worm_commands.read() {
+            match *command {
+                WormCommand::LetGo => controls.let_go = true,
+                WormCommand::Climb(amount) => controls.climb = amount.clamp(-1.0, 1.0),
+                WormCommand::Move(direction) => controls.sideways = direction.clamp(-1.0, 1.0),
+                _ => {}
+            }
+        }
+        controls
+    }
+}
+
+fn ninja_rope_physics(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut worm_commands: EventReader<WormCommand>,
+    mut turn: TurnFlow,
+    mut rope_query: Query<(Entity, &mut Transform, &mut RigidBody, &mut RopeAttachment)>,
+) {
+    let controls = ToolControls::read(&mut worm_commands);
+
+    for (entity, mut transform, mut body, mut rope) in rope_query.iter_mut() {
+        // Letting go keeps the swing momentum
+        if controls.let_go {
+            commands.entity(entity).remove::<RopeAttachment>();
+            turn.finish_tool_use();
+            continue;
+        }
+
+        // Climb up and down the rope
+        rope.length -= controls.climb * ROPE_CLIMB_SPEED * time.delta_secs();
+        rope.length = rope.length.clamp(ROPE_MIN_LENGTH, ROPE_MAX_LENGTH);
+
+        let offset = transform.translation.truncate() - rope.anchor;
+        let Some(radial) = offset.try_normalize() else {
+            continue;
+        };
+        let tangent = Vec2::new(-radial.y, radial.x);
+
+        // Swing left and right. Tangent points counter-clockwise, flip it so "right"
+        // pushes the worm to the right
+        let push_right = if tangent.x >= 0.0 { tangent } else { -tangent };
+        body.velocity += push_right * controls.sideways * ROPE_SWING_FORCE * time.delta_secs();
+
+        // Keep the worm on the rope: clamp to the rope length and drop outward velocity
+        if offset.length() > rope.length {
+            let constrained = rope.anchor + radial * rope.length;
+            transform.translation.x = constrained.x;
+            transform.translation.y = constrained.y;
+
+            let outward_speed = body.velocity.dot(radial);
+            if outward_speed > 0.0 {
+                body.velocity -= radial * outward_speed;
+            }
+        }
+    }
+}
+
+fn jetpack_flight(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut worm_commands: EventReader<WormCommand>,
+    mut turn: TurnFlow,
+    mut jetpack_query: Query<(Entity, &mut RigidBody, &Collider, &mut JetpackFlight)>,
+) {
+    let controls = ToolControls::read(&mut worm_commands);
+
+    for (entity, mut body, collider, mut jetpack) in jetpack_query.iter_mut() {
+        let mut thrusting = false;
+
+        if jetpack.fuel > 0.0 {
+            if controls.climb > 0.0 {
+                body.velocity.y += JETPACK_THRUST * time.delta_secs();
+                thrusting = true;
+            }
+            if controls.sideways != 0.0 {
+                body.velocity.x = controls.sideways.signum() * JETPACK_STRAFE;
+                thrusting = true;
+            }
+        }
+
+        if thrusting {
+            jetpack.fuel = (jetpack.fuel - time.delta_secs()).max(0.0);
+        }
+
+        // Switching the jetpack off, landing or running dry ends the flight
+        let out_of_fuel = jetpack.fuel <= 0.0 && collider.is_grounded;
+        let landed = collider.is_grounded && !thrusting && jetpack.fuel < jetpack.max_fuel;
+        if controls.let_go || out_of_fuel || landed {
+            commands.entity(entity).remove::<JetpackFlight>();
+            turn.finish_tool_use();
+        }
+    }
+}
+
+fn release_tools(
+    mut commands: Commands,
+    rope_query: Query<Entity, With<RopeAttachment>>,
+    jetpack_query: Query<Entity, With<JetpackFlight>>,
+) {
+    for entity in rope_query.iter() {
+        commands.entity(entity).remove::<RopeAttachment>();
+    }
+    for entity in jetpack_query.iter() {
+        commands.entity(entity).remove::<JetpackFlight>();
+    }
+}
+
+fn reset_tool_uses(mut tool_state: ResMut<ToolState>) {
+    tool_state.girders_placed = 0;
+    tool_state.teleports_used = 0;
+}
+
+fn update_rope_visual(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    rope_query: Query<(&Transform, &RopeAttachment)>,
+    visual_query: Query<Entity, With<RopeVisual>>,
+) {
+    for entity in visual_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for (transform, rope) in rope_query.iter() {
+        let worm_position = transform.translation.truncate();
+        let span = worm_position - rope.anchor;
+        let midpoint = rope.anchor + span / 2.0;
+
+        commands.spawn((
+            Mesh2d(meshes.add(bevy::math::primitives::Rectangle::new(span.length(), 2.0))),
+            MeshMaterial2d(materials.add(ColorMaterial::from(WeaponType::NinjaRope.get_color()))),
+            Transform::from_translation(midpoint.extend(0.6))
+                .with_rotation(Quat::from_rotation_z(span.y.atan2(span.x))),
+            RopeVisual,
+        ));
+    }
+}
+
+fn update_girder_preview(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    turn: TurnFlow,
+    tool_state: Res<ToolState>,
+    weapon_inventory: Res<WeaponInventory>,
+    world_cursor: WorldCursor,
+    preview_query: Query<Entity, With<GirderPreview>>,
+) {
+    for entity in preview_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let girder_selected = matches!(
+        weapon_inventory.weapons.get(weapon_inventory.current_weapon),
+        Some(WeaponType::Girder)
+    );
+    if !girder_selected
+        || !turn.can_player_act()
+        || tool_state.girders_placed >= tool_state.girders_per_turn
+    {
+        return;
+    }
+
+    if let Some(cursor) = world_cursor.position() {
+        commands.spawn((
+            Mesh2d(meshes.add(bevy::math::primitives::Rectangle::new(GIRDER_LENGTH, GIRDER_THICKNESS))),
+            MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgba(0.5, 0.5, 0.6, 0.4)))),
+            Transform::from_translation(cursor.extend(0.5))
+                .with_rotation(Quat::from_rotation_z(tool_state.girder_angle.to_radians())),
+            GirderPreview,
+        ));
+    }
+}
@@ -1,6 +1,8 @@
 use bevy::prelude::*;
-use crate::game::game_state::{GameState, GamePhase, TurnTimer};
-use crate::game::worm::Worm;
+use bevy::color::palettes::css;
+use crate::game::menu::AppState;
+use crate::game::replay::simulation_running;
+use crate::game::game_state::{GameState, GamePhase, TurnTimer, RoundTimer};
 use crate::game::weapons::WeaponInventory;
 use crate::game::aiming::AimingState;
 
@@ -9,7 +11,7 @@ pub struct UIPlugin;
 impl Plugin for UIPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(Startup, setup_ui)
+            .add_systems(OnEnter(AppState::InGame), setup_ui)
             .add_systems(Update, (
                 update_turn_timer_ui,
                 update_health_bars_ui,
@@ -17,7 +19,7 @@ impl Plugin for UIPlugin {
                 update_game_phase_ui,
                 update_power_meter_ui,
                 update_wind_indicator_ui,
-            ));
+            ).run_if(simulation_running));
     }
 }
 
@@ -25,9 +27,6 @@ impl Plugin for UIPlugin {
 #[derive(Component)]
 pub struct TurnTimerText;
 
-#[derive(Component)]
-pub struct HealthBarUI;
-
 #[derive(Component)]
 pub struct WeaponSelectionUI;
 
@@ -37,6 +36,9 @@ pub struct GamePhaseText;
 #[derive(Component)]
 pub struct PowerMeterUI;
 
+#[derive(Component)]
+pub struct PowerFillUI;
+
 #[derive(Component)]
 pub struct WindIndicatorUI;
 
@@ -45,22 +47,27 @@ pub struct GameOverUI;
 
 fn setup_ui(mut commands: Commands) {
     // Root UI container
-    commands.spawn(Node {
-        width: Val::Percent(100.0),
-        height: Val::Percent(100.0),
-        position_type: PositionType::Absolute,
-        ..default()
-    }).with_children(|parent| {
-        // Top bar - Turn timer and game phase
-        parent.spawn(Node {
+    commands.spawn((
+        Node {
             width: Val::Percent(100.0),
-            height: Val::Px(60.0),
-            background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
-            justify_content: JustifyContent::SpaceBetween,
-            align_items: AlignItems::Center,
-            padding: UiRect::all(Val::Px(10.0)),
+            height: Val::Percent(100.0),
+            position_type: PositionType::Absolute,
             ..default()
-        }).with_children(|top_bar| {
+        },
+        StateScoped(AppState::InGame),
+    )).with_children(|parent| {
+        // Top bar - Turn timer and game phase
+        parent.spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Px(60.0),
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+        )).with_children(|top_bar| {
             // Turn timer
             top_bar.spawn((
                 Text::new("Turn: 30s"),
@@ -79,23 +86,25 @@ fn setup_ui(mut commands: Commands) {
                     font_size: 20.0,
                     ..default()
                 },
-                TextColor(Color::YELLOW),
+                TextColor(css::YELLOW.into()),
                 GamePhaseText,
             ));
         });
         
         // Bottom bar - Weapon selection and power meter
-        parent.spawn(Node {
-            width: Val::Percent(100.0),
-            height: Val::Px(80.0),
-            background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
-            position_type: PositionType::Absolute,
-            bottom: Val::Px(0.0),
-            justify_content: JustifyContent::SpaceBetween,
-            align_items: AlignItems::Center,
-            padding: UiRect::all(Val::Px(10.0)),
-            ..default()
-        }).with_children(|bottom_bar| {
+        parent.spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Px(80.0),
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(0.0),
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+        )).with_children(|bottom_bar| {
             // Weapon selection
             bottom_bar.spawn((
                 Text::new("Weapon: Grenade (1/2/3 to switch)"),
@@ -112,11 +121,11 @@ fn setup_ui(mut commands: Commands) {
                 Node {
                     width: Val::Px(200.0),
                     height: Val::Px(20.0),
-                    background_color: Color::srgba(0.3, 0.3, 0.3, 0.8).into(),
                     border: UiRect::all(Val::Px(2.0)),
                     display: Display::None,
                     ..default()
                 },
+                BackgroundColor(Color::srgba(0.3, 0.3, 0.3, 0.8)),
                 BorderColor(Color::WHITE),
                 PowerMeterUI,
             )).with_children(|power_bar| {
@@ -125,9 +134,10 @@ fn setup_ui(mut commands: Commands) {
                     Node {
                         width: Val::Percent(0.0),
                         height: Val::Percent(100.0),
-                        background_color: Color::srgb(0.2, 0.8, 0.2).into(),
                         ..default()
                     },
+                    BackgroundColor(Color::srgb(0.2, 0.8, 0.2)),
+                    PowerFillUI,
                 ));
             });
         });
@@ -137,7 +147,6 @@ fn setup_ui(mut commands: Commands) {
             Node {
                 width: Val::Px(150.0),
                 height: Val::Px(40.0),
-                background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
                 position_type: PositionType::Absolute,
                 top: Val::Px(70.0),
                 right: Val::Px(10.0),
@@ -145,6 +154,7 @@ fn setup_ui(mut commands: Commands) {
                 align_items: AlignItems::Center,
                 ..default()
             },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
             WindIndicatorUI,
         )).with_children(|wind| {
             wind.spawn((
@@ -153,7 +163,7 @@ fn setup_ui(mut commands: Commands) {
                     font_size: 16.0,
                     ..default()
                 },
-                TextColor(Color::CYAN),
+                TextColor(css::AQUA.into()),
             ));
         });
     });
@@ -161,21 +171,24 @@ fn setup_ui(mut commands: Commands) {
 
 fn update_turn_timer_ui(
     timer: Res<TurnTimer>,
+    round_timer: Res<RoundTimer>,
     game_state: Res<GameState>,
     mut query: Query<&mut Text, With<TurnTimerText>>,
 ) {
     for mut text in query.iter_mut() {
         let time_left = timer.current_time.max(0.0) as i32;
-        let current_team = game_state.current_player + 1;
-        **text = format!("Player {} - Time: {}s", current_team, time_left);
+        let current_team = game_state.team_name(game_state.current_player);
+        let round = if round_timer.sudden_death {
+            "SUDDEN DEATH".to_string()
+        } else {
+            let seconds = round_timer.time_left as i32;
+            format!("Round: {}:{:02}", seconds / 60, seconds % 60)
+        };
+        **text = format!("{} - Time: {}s - {}", current_team, time_left, round);
     }
 }
 
-fn update_health_bars_ui(
-    worm_query: Query<&Worm>,
-    game_state: Res<GameState>,
-    // We'll implement visual health bars in the world, not UI
-) {
+fn update_health_bars_ui() {
     // Health bars are already implemented as world-space UI in worm.rs
     // This could be enhanced to show a summary in the UI panel
 }
@@ -188,27 +201,25 @@ fn update_weapon_selection_ui(
         if let Some(current_weapon) = weapon_inventory.weapons.get(weapon_inventory.current_weapon) {
             **text = format!(
                 "Weapon: {} (1/2/3 to switch, Q/E to cycle)",
-                match current_weapon {
-                    crate::game::weapons::WeaponType::Grenade => "Grenade",
-                    crate::game::weapons::WeaponType::Bazooka => "Bazooka",
-                    crate::game::weapons::WeaponType::ClusterBomb => "Cluster Bomb",
-                }
+                current_weapon.name()
             );
         }
     }
 }
 
 fn update_game_phase_ui(
-    game_state: Res<GameState>,
+    phase: Res<State<GamePhase>>,
     mut query: Query<&mut Text, With<GamePhaseText>>,
 ) {
     for mut text in query.iter_mut() {
-        **text = match game_state.game_phase {
+        **text = match phase.get() {
             GamePhase::PlayerTurn => "Your Turn - Move & Aim".to_string(),
             GamePhase::Aiming => "Aiming - Use arrows, Enter to charge".to_string(),
-            GamePhase::Firing => "Firing!".to_string(),
             GamePhase::ProjectileFlying => "Projectile Flying...".to_string(),
             GamePhase::Explosion => "BOOM!".to_string(),
+            GamePhase::Retreat => "Retreat!".to_string(),
+            GamePhase::UsingTool => "Using Tool...".to_string(),
+            GamePhase::TurnResolution => "Counting Damage...".to_string(),
             GamePhase::TurnTransition => "Turn Ending...".to_string(),
             GamePhase::GameOver => "Game Over!".to_string(),
         };
@@ -218,23 +229,23 @@ fn update_game_phase_ui(
 fn update_power_meter_ui(
     aiming_state: Res<AimingState>,
     mut power_meter_query: Query<&mut Node, With<PowerMeterUI>>,
-    mut power_fill_query: Query<&mut Node, (Without<PowerMeterUI>, With<Node>)>,
+    mut power_fill_query: Query<(&mut Node, &mut BackgroundColor), (With<PowerFillUI>, Without<PowerMeterUI>)>,
 ) {
     for mut power_meter in power_meter_query.iter_mut() {
         if aiming_state.is_aiming && aiming_state.power_charging {
             power_meter.display = Display::Flex;
             
             // Update power fill
-            for mut fill in power_fill_query.iter_mut() {
+            for (mut fill, mut fill_color) in power_fill_query.iter_mut() {
                 fill.width = Val::Percent(aiming_state.power * 100.0);
                 
                 // Change color based on power level
                 if aiming_state.power < 0.7 {
-                    fill.background_color = Color::srgb(0.2, 0.8, 0.2).into();
+                    fill_color.0 = Color::srgb(0.2, 0.8, 0.2);
                 } else if aiming_state.power < 0.9 {
-                    fill.background_color = Color::srgb(0.8, 0.8, 0.2).into();
+                    fill_color.0 = Color::srgb(0.8, 0.8, 0.2);
                 } else {
-                    fill.background_color = Color::srgb(0.8, 0.2, 0.2).into();
+                    fill_color.0 = Color::srgb(0.8, 0.2, 0.2);
                 }
             }
         } else {
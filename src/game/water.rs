@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use crate::game::replay::simulation_running;
+use crate::game::game_state::GamePhase;
+use crate::game::scheme::GameScheme;
+use crate::game::terrain::TerrainMap;
+use crate::game::worm::Worm;
+use crate::game::physics::GROUND_Y;
+use crate::game::events::{ApplyDamage, DamageCause, SuddenDeathStarted};
+
+pub struct WaterPlugin;
+
+impl Plugin for WaterPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(WaterLevel::default())
+            .add_systems(Update, (
+                start_rising_water,
+                animate_water_level,
+                drown_worms,
+            ).chain().run_if(simulation_running))
+            .add_systems(OnEnter(GamePhase::TurnTransition), raise_water);
+    }
+}
+
+/// Sudden death water. The surface creeps up towards `target` a little every turn.
+#[derive(Resource)]
+pub struct WaterLevel {
+    pub level: f32,
+    pub target: f32,
+    pub rising: bool,
+}
+
+impl Default for WaterLevel {
+    fn default() -> Self {
+        Self {
+            level: GROUND_Y,
+            target: GROUND_Y,
+            rising: false,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Water {
+    pub height: f32,
+}
+
+// How fast the surface moves towards its target, in pixels per second
+const WATER_RISE_SPEED: f32 = 30.0;
+
+fn start_rising_water(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut sudden_death_events: EventReader<SuddenDeathStarted>,
+    mut water: ResMut<WaterLevel>,
+    scheme: Res<GameScheme>,
+    terrain: Res<TerrainMap>,
+) {
+    for event in sudden_death_events.read() {
+        if !event.effect.raises_water() || water.rising {
+            continue;
+        }
+
+        water.rising = true;
+        water.target = water.level + scheme.water_rise_per_turn;
+
+        spawn_water_sheet(&mut commands, &mut meshes, &mut materials, &terrain, water.level);
+    }
+}
+
+/// One tall sheet whose top edge is the water surface.
+pub fn spawn_water_sheet(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    terrain: &TerrainMap,
+    level: f32,
+) {
+    let width = terrain.width as f32 * 2.0;
+    let height = terrain.height as f32 * 2.0;
+    commands.spawn((
+        Mesh2d(meshes.add(bevy::math::primitives::Rectangle::new(width, height))),
+        MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgba(0.1, 0.3, 0.8, 0.6)))),
+        Transform::from_translation(Vec3::new(0.0, level - height / 2.0, 3.0)),
+        Water { height },
+    ));
+}
+
+fn raise_water(
+    mut water: ResMut<WaterLevel>,
+    scheme: Res<GameScheme>,
+    terrain: Res<TerrainMap>,
+) {
+    if !water.rising {
+        return;
+    }
+
+    let top = terrain.height as f32 / 2.0;
+    water.target = (water.target + scheme.water_rise_per_turn).min(top);
+}
+
+fn animate_water_level(
+    time: Res<Time>,
+    mut water: ResMut<WaterLevel>,
+    mut water_query: Query<(&mut Transform, &Water)>,
+) {
+    if water.level < water.target {
+        water.level = (water.level + WATER_RISE_SPEED * time.delta_secs()).min(water.target);
+    }
+
+    for (mut transform, sheet) in water_query.iter_mut() {
+        transform.translation.y = water.level - sheet.height / 2.0;
+    }
+}
+
+/// Worms that go under never come back up.
+fn drown_worms(
+    water: Res<WaterLevel>,
+    mut damage_events: EventWriter<ApplyDamage>,
+    worm_query: Query<(Entity, &Transform, &Worm)>,
+) {
+    if !water.rising {
+        return;
+    }
+
+    for (entity, transform, worm) in worm_query.iter() {
+        let remaining_health = worm.health - worm.pending_damage;
+        if transform.translation.y < water.level && remaining_health > 0.0 {
+            damage_events.write(ApplyDamage {
+                target: entity,
+                amount: remaining_health,
+                source_team: None,
+                cause: DamageCause::Drowning,
+            });
+        }
+    }
+}
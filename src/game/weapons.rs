@@ -1,7 +1,11 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::game::replay::simulation_running;
 use crate::game::physics::{RigidBody, Collider};
 
 use crate::game::worm::Worm;
+use crate::game::events::{ApplyDamage, DamageCause, ProjectileFired, TerrainDestroyed};
+use crate::game::lifecycle::MatchRng;
 
 pub struct WeaponPlugin;
 
@@ -16,15 +20,42 @@ impl Plugin for WeaponPlugin {
                 projectile_collision,
                 explosion_system,
                 cleanup_expired_projectiles,
-                change_wind_on_turn_end,
-            ));
+            ).run_if(simulation_running))
+            .add_systems(OnEnter(crate::game::game_state::GamePhase::TurnTransition), change_wind_on_turn_end)
+            .add_systems(OnTransition {
+                exited: crate::game::game_state::GamePhase::TurnTransition,
+                entered: crate::game::game_state::GamePhase::PlayerTurn,
+            }, hand_weapons_to_team);
     }
 }
 
+/// The arsenal every team picks from. Each team keeps its own pick between turns;
+/// `current_weapon` is the pick of the team that `team` names.
 #[derive(Resource)]
 pub struct WeaponInventory {
     pub weapons: Vec<WeaponType>,
     pub current_weapon: usize,
+    pub team: u32,
+    /// Per team, in team order; the pick each team last left its turn with
+    pub team_weapons: Vec<usize>,
+}
+
+impl WeaponInventory {
+    /// Each team's pick, with the one being made now filled in.
+    pub fn picks(&self, team_count: usize) -> Vec<usize> {
+        let mut picks = self.team_weapons.clone();
+        picks.resize(team_count.max(self.team as usize + 1), 0);
+        picks[self.team as usize] = self.current_weapon;
+        picks.truncate(team_count);
+        picks
+    }
+
+    /// Puts the last team's pick away and takes out `team`'s.
+    pub fn hand_to(&mut self, team: u32, team_count: usize) {
+        self.team_weapons = self.picks(team_count);
+        self.team = team;
+        self.current_weapon = self.team_weapons.get(team as usize).copied().unwrap_or(0);
+    }
 }
 
 impl Default for WeaponInventory {
@@ -34,8 +65,21 @@ impl Default for WeaponInventory {
                 WeaponType::Bazooka,
                 WeaponType::Grenade,
                 WeaponType::Shotgun,
+                WeaponType::PetrolBomb,
+                WeaponType::Airstrike,
+                WeaponType::NapalmStrike,
+                WeaponType::MailStrike,
+                WeaponType::Dynamite,
+                WeaponType::Mine,
+                WeaponType::Sheep,
+                WeaponType::NinjaRope,
+                WeaponType::Jetpack,
+                WeaponType::Girder,
+                WeaponType::Teleport,
             ],
             current_weapon: 0,
+            team: 0,
+            team_weapons: Vec::new(),
         }
     }
 }
@@ -54,21 +98,86 @@ impl WindSystem {
         }
     }
     
-    pub fn generate_new_wind(&mut self) {
-        let strength = (fastrand::f32() - 0.5) * 120.0; // -60 to +60 wind force
+    pub fn generate_new_wind(&mut self, rng: &mut fastrand::Rng, max_strength: f32) {
+        let strength = (rng.f32() - 0.5) * 2.0 * max_strength; // -max to +max wind force
         self.force = Vec2::new(strength, 0.0); // Only horizontal wind
         println!("New wind: {:.1}", strength); // Debug output
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum WeaponType {
     Grenade,
     Bazooka,
+    Shotgun,
     ClusterBomb,
+    PetrolBomb,
+    Airstrike,
+    NapalmStrike,
+    MailStrike,
+    Dynamite,
+    Mine,
+    Sheep,
+    NinjaRope,
+    Jetpack,
+    Girder,
+    Teleport,
+}
+
+/// How a weapon is deployed, which decides the input flow used to fire it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WeaponCategory {
+    /// Launched from the worm using angle and power aiming
+    Projectile,
+    /// Called in on a clicked map point, bombs fall from above the map
+    TargetedStrike,
+    /// Dropped at the worm's feet and set off by a fuse, proximity or command
+    Placed,
+    /// Utilities that use the turn without ending it
+    Tool,
+}
+
+#[derive(Clone)]
+pub struct StrikeStats {
+    pub bomb_count: u32,
+    pub bomb_spacing: f32,
+    pub horizontal_speed: f32,
 }
 
 impl WeaponType {
+    pub fn category(&self) -> WeaponCategory {
+        match self {
+            WeaponType::Airstrike | WeaponType::NapalmStrike | WeaponType::MailStrike => {
+                WeaponCategory::TargetedStrike
+            }
+            WeaponType::Dynamite | WeaponType::Mine | WeaponType::Sheep => WeaponCategory::Placed,
+            WeaponType::NinjaRope | WeaponType::Jetpack | WeaponType::Girder | WeaponType::Teleport => {
+                WeaponCategory::Tool
+            }
+            _ => WeaponCategory::Projectile,
+        }
+    }
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponType::Grenade => "Grenade",
+            WeaponType::Bazooka => "Bazooka",
+            WeaponType::Shotgun => "Shotgun",
+            WeaponType::ClusterBomb => "Cluster Bomb",
+            WeaponType::PetrolBomb => "Petrol Bomb",
+            WeaponType::Airstrike => "Airstrike",
+            WeaponType::NapalmStrike => "Napalm Strike",
+            WeaponType::MailStrike => "Mail Strike",
+            WeaponType::Dynamite => "Dynamite",
+            WeaponType::Mine => "Mine",
+            WeaponType::Sheep => "Sheep",
+            WeaponType::NinjaRope => "Ninja Rope",
+            WeaponType::Jetpack => "Jetpack",
+            WeaponType::Girder => "Girder",
+            WeaponType::Teleport => "Teleport",
+        }
+    }
+    
     pub fn get_stats(&self) -> WeaponStats {
         match self {
             WeaponType::Bazooka => WeaponStats {
@@ -89,6 +198,15 @@ impl WeaponType {
                 fuse_time: Some(3.0),
                 projectile_count: 1,
             },
+            WeaponType::Shotgun => WeaponStats {
+                damage: 20.0,
+                explosion_radius: 25.0,
+                projectile_speed: 900.0,
+                gravity_scale: 0.2,
+                wind_resistance: 0.0,
+                fuse_time: None,
+                projectile_count: 3,
+            },
             WeaponType::ClusterBomb => WeaponStats {
                 damage: 35.0,
                 explosion_radius: 60.0,
@@ -98,6 +216,113 @@ impl WeaponType {
                 fuse_time: Some(2.5),
                 projectile_count: 3, // Splits into 3 smaller bombs
             },
+            WeaponType::PetrolBomb => WeaponStats {
+                damage: 10.0,
+                explosion_radius: 20.0,
+                projectile_speed: 450.0,
+                gravity_scale: 1.0,
+                wind_resistance: 0.6,
+                fuse_time: None,
+                projectile_count: 1,
+            },
+            // Strike stats describe a single bomb; see `strike_stats` for the formation
+            WeaponType::Airstrike => WeaponStats {
+                damage: 30.0,
+                explosion_radius: 50.0,
+                projectile_speed: 0.0,
+                gravity_scale: 1.0,
+                wind_resistance: 0.3,
+                fuse_time: None,
+                projectile_count: 5,
+            },
+            WeaponType::NapalmStrike => WeaponStats {
+                damage: 5.0,
+                explosion_radius: 15.0, // Most of the damage comes from the fire it leaves
+                projectile_speed: 0.0,
+                gravity_scale: 0.8,
+                wind_resistance: 0.6,
+                fuse_time: None,
+                projectile_count: 8,
+            },
+            WeaponType::MailStrike => WeaponStats {
+                damage: 25.0,
+                explosion_radius: 45.0,
+                projectile_speed: 0.0,
+                gravity_scale: 0.4, // Letters flutter down slowly
+                wind_resistance: 1.5,
+                fuse_time: None,
+                projectile_count: 5,
+            },
+            // Placed weapons are dropped rather than launched, so they have no speed or wind
+            WeaponType::Dynamite => WeaponStats {
+                damage: 75.0,
+                explosion_radius: 110.0,
+                projectile_speed: 0.0,
+                gravity_scale: 1.0,
+                wind_resistance: 0.0,
+                fuse_time: Some(5.0),
+                projectile_count: 1,
+            },
+            WeaponType::Mine => WeaponStats {
+                damage: 45.0,
+                explosion_radius: 70.0,
+                projectile_speed: 0.0,
+                gravity_scale: 1.0,
+                wind_resistance: 0.0,
+                fuse_time: Some(1.0), // Delay between triggering and exploding
+                projectile_count: 1,
+            },
+            WeaponType::Sheep => WeaponStats {
+                damage: 70.0,
+                explosion_radius: 100.0,
+                projectile_speed: 0.0,
+                gravity_scale: 1.0,
+                wind_resistance: 0.0,
+                fuse_time: Some(10.0), // Sheep blows up on its own if never detonated
+                projectile_count: 1,
+            },
+            // Tools never explode
+            WeaponType::NinjaRope | WeaponType::Jetpack | WeaponType::Girder | WeaponType::Teleport => WeaponStats {
+                damage: 0.0,
+                explosion_radius: 0.0,
+                projectile_speed: 0.0,
+                gravity_scale: 1.0,
+                wind_resistance: 0.0,
+                fuse_time: None,
+                projectile_count: 0,
+            },
+        }
+    }
+    
+    /// Number of burning napalm particles left behind when this weapon goes off.
+    pub fn fire_count(&self) -> usize {
+        match self {
+            WeaponType::PetrolBomb => 12,
+            WeaponType::NapalmStrike => 4,
+            _ => 0,
+        }
+    }
+    
+    /// Bomb formation for targeted strikes, `None` for weapons fired from the worm.
+    pub fn strike_stats(&self) -> Option<StrikeStats> {
+        let stats = self.get_stats();
+        match self {
+            WeaponType::Airstrike => Some(StrikeStats {
+                bomb_count: stats.projectile_count,
+                bomb_spacing: 40.0,
+                horizontal_speed: 250.0,
+            }),
+            WeaponType::NapalmStrike => Some(StrikeStats {
+                bomb_count: stats.projectile_count,
+                bomb_spacing: 20.0,
+                horizontal_speed: 200.0,
+            }),
+            WeaponType::MailStrike => Some(StrikeStats {
+                bomb_count: stats.projectile_count,
+                bomb_spacing: 35.0,
+                horizontal_speed: 150.0,
+            }),
+            _ => None,
         }
     }
     
@@ -105,7 +330,19 @@ impl WeaponType {
         match self {
             WeaponType::Grenade => Color::srgb(0.2, 0.8, 0.2),
             WeaponType::Bazooka => Color::srgb(1.0, 0.5, 0.0),
+            WeaponType::Shotgun => Color::srgb(0.9, 0.9, 0.9),
             WeaponType::ClusterBomb => Color::srgb(0.8, 0.2, 0.8),
+            WeaponType::PetrolBomb => Color::srgb(0.9, 0.6, 0.1),
+            WeaponType::Airstrike => Color::srgb(0.3, 0.3, 0.3),
+            WeaponType::NapalmStrike => Color::srgb(1.0, 0.3, 0.0),
+            WeaponType::MailStrike => Color::srgb(0.95, 0.95, 0.85),
+            WeaponType::Dynamite => Color::srgb(0.9, 0.1, 0.1),
+            WeaponType::Mine => Color::srgb(0.2, 0.2, 0.2),
+            WeaponType::Sheep => Color::srgb(1.0, 1.0, 1.0),
+            WeaponType::NinjaRope => Color::srgb(0.6, 0.4, 0.2),
+            WeaponType::Jetpack => Color::srgb(0.2, 0.6, 1.0),
+            WeaponType::Girder => Color::srgb(0.5, 0.5, 0.6),
+            WeaponType::Teleport => Color::srgb(0.6, 0.2, 1.0),
         }
     }
 }
@@ -124,6 +361,7 @@ pub struct WeaponStats {
 #[derive(Component)]
 pub struct Projectile {
     pub weapon_type: WeaponType,
+    pub owner_team: Option<u32>,
     pub damage: f32,
     pub explosion_radius: f32,
     pub fuse_timer: Option<Timer>,
@@ -136,6 +374,9 @@ pub struct Explosion {
     pub radius: f32,
     pub damage: f32,
     pub lifetime: Timer,
+    pub damage_applied: bool,
+    pub source_team: Option<u32>,
+    pub cause: DamageCause,
 }
 
 pub fn fire_weapon(
@@ -143,6 +384,7 @@ pub fn fire_weapon(
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<ColorMaterial>>,
     weapon_type: WeaponType,
+    owner_team: Option<u32>,
     position: Vec3,
     direction: Vec2,
     power: f32,
@@ -167,36 +409,65 @@ pub fn fire_weapon(
             );
         }
         
-        let fuse_timer = stats.fuse_time.map(|time| Timer::from_seconds(time, TimerMode::Once));
-        
-        commands.spawn((
-            Mesh2d(meshes.add(bevy::math::primitives::Circle::new(4.0))),
-            MeshMaterial2d(materials.add(ColorMaterial::from(weapon_type.get_color()))),
-            Transform::from_translation(position),
-            Projectile {
-                weapon_type: weapon_type.clone(),
-                damage: stats.damage,
-                explosion_radius: stats.explosion_radius,
-                fuse_timer,
-                wind_resistance: stats.wind_resistance,
-                has_exploded: false,
-            },
-            RigidBody {
-                velocity: projectile_velocity,
-                gravity_scale: stats.gravity_scale,
-                mass: 0.1,
-                bounce: 0.3,
-                friction: 0.9,
-            },
-            Collider {
-                radius: 4.0,
-                is_grounded: false,
-            },
-        ));
+        spawn_projectile(
+            commands,
+            meshes,
+            materials,
+            weapon_type.clone(),
+            owner_team,
+            position,
+            projectile_velocity,
+        );
     }
 }
 
-fn projectile_movement(
+/// Spawns a single projectile of `weapon_type` with the given launch velocity.
+pub fn spawn_projectile(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    weapon_type: WeaponType,
+    owner_team: Option<u32>,
+    position: Vec3,
+    velocity: Vec2,
+) {
+    let stats = weapon_type.get_stats();
+    let fuse_timer = stats.fuse_time.map(|time| Timer::from_seconds(time, TimerMode::Once));
+    
+    commands.spawn((
+        Mesh2d(meshes.add(bevy::math::primitives::Circle::new(4.0))),
+        MeshMaterial2d(materials.add(ColorMaterial::from(weapon_type.get_color()))),
+        Transform::from_translation(position),
+        Projectile {
+            weapon_type: weapon_type.clone(),
+            owner_team,
+            damage: stats.damage,
+            explosion_radius: stats.explosion_radius,
+            fuse_timer,
+            wind_resistance: stats.wind_resistance,
+            has_exploded: false,
+        },
+        RigidBody {
+            velocity,
+            gravity_scale: stats.gravity_scale,
+            bounce: 0.3,
+            friction: 0.9,
+        },
+        Collider {
+            radius: 4.0,
+            is_grounded: false,
+        },
+    ));
+    
+    commands.send_event(ProjectileFired {
+        team: owner_team,
+        weapon: weapon_type,
+        position: position.truncate(),
+        velocity,
+    });
+}
+
+fn apply_wind_to_projectiles(
     time: Res<Time>,
     wind: Res<WindSystem>,
     mut query: Query<(&mut RigidBody, &Projectile)>,
@@ -211,8 +482,9 @@ fn projectile_collision(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    mut terrain: ResMut<crate::game::terrain::TerrainMap>,
-    mut game_state: ResMut<crate::game::game_state::GameState>,
+    mut rng: ResMut<MatchRng>,
+    terrain: Res<crate::game::terrain::TerrainMap>,
+    mut turn: crate::game::game_state::TurnFlow,
     mut projectile_query: Query<(Entity, &Transform, &mut Projectile, &Collider)>,
     time: Res<Time>,
 ) {
@@ -229,8 +501,8 @@ fn projectile_collision(
                     &mut commands,
                     &mut meshes,
                     &mut materials,
-                    &mut terrain,
-                    &mut game_state,
+                    &mut rng,
+                    &mut turn,
                     entity,
                     transform.translation,
                     &projectile,
@@ -248,8 +520,8 @@ fn projectile_collision(
                 &mut commands,
                 &mut meshes,
                 &mut materials,
-                &mut terrain,
-                &mut game_state,
+                &mut rng,
+                &mut turn,
                 entity,
                 transform.translation,
                 &projectile,
@@ -262,8 +534,8 @@ fn explode_projectile(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<ColorMaterial>>,
-    terrain: &mut ResMut<crate::game::terrain::TerrainMap>,
-    game_state: &mut ResMut<crate::game::game_state::GameState>,
+    rng: &mut fastrand::Rng,
+    turn: &mut crate::game::game_state::TurnFlow,
     projectile_entity: Entity,
     position: Vec3,
     projectile: &Projectile,
@@ -271,21 +543,51 @@ fn explode_projectile(
     // Remove projectile
     commands.entity(projectile_entity).despawn();
     
-    // Destroy terrain in explosion radius
-    let world_x = position.x + (terrain.width as f32 / 2.0);
-    let world_y = position.y + (terrain.height as f32 / 2.0);
-    terrain.destroy_circle(world_x, world_y, projectile.explosion_radius);
+    detonate(
+        commands,
+        meshes,
+        materials,
+        rng,
+        turn,
+        position,
+        projectile.explosion_radius,
+        projectile.damage,
+        projectile.owner_team,
+        DamageCause::Weapon(projectile.weapon_type.clone()),
+    );
     
+    // Napalm weapons leave burning fire behind
+    let fire_count = projectile.weapon_type.fire_count();
+    if fire_count > 0 {
+        crate::game::fire::spawn_fire(commands, meshes, materials, rng, position, fire_count, projectile.owner_team);
+    }
+}
+
+/// Spawns an `Explosion` that damages everything in range and carves the terrain.
+/// Shared by projectiles and anything else that blows up.
+pub fn detonate(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    rng: &mut fastrand::Rng,
+    turn: &mut crate::game::game_state::TurnFlow,
+    position: Vec3,
+    explosion_radius: f32,
+    damage: f32,
+    source_team: Option<u32>,
+    cause: DamageCause,
+) {
     // Update game state
-    game_state.explosion_started();
+    turn.explosion_started();
     
     // Spawn explosion particles
     crate::game::particles::spawn_explosion_particles(
         commands,
         meshes,
         materials,
+        rng,
         position,
-        (projectile.explosion_radius / 5.0) as usize, // Scale particle count with explosion size
+        (explosion_radius / 5.0) as usize, // Scale particle count with explosion size
     );
     
     // Spawn dirt particles
@@ -293,54 +595,120 @@ fn explode_projectile(
         commands,
         meshes,
         materials,
+        rng,
         position,
-        (projectile.explosion_radius / 8.0) as usize,
+        (explosion_radius / 8.0) as usize,
     );
     
     // Create explosion effect
     commands.spawn((
-        Mesh2d(meshes.add(bevy::math::primitives::Circle::new(projectile.explosion_radius))),
+        Mesh2d(meshes.add(bevy::math::primitives::Circle::new(explosion_radius))),
         MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgba(1.0, 0.5, 0.0, 0.6)))),
         Transform::from_translation(position),
         Explosion {
-            radius: projectile.explosion_radius,
-            damage: projectile.damage,
+            radius: explosion_radius,
+            damage,
             lifetime: Timer::from_seconds(0.5, TimerMode::Once),
+            damage_applied: false,
+            source_team,
+            cause,
         },
     ));
 }
 
+// Launch speed given to a gravestone at the center of a blast
+const GRAVESTONE_KNOCKBACK: f32 = 500.0;
+
 fn explosion_system(
     mut commands: Commands,
     time: Res<Time>,
-    mut game_state: ResMut<crate::game::game_state::GameState>,
+    damage_model: Res<crate::game::damage::DamageModel>,
+    mut terrain: ResMut<crate::game::terrain::TerrainMap>,
+    mut damage_events: EventWriter<ApplyDamage>,
+    mut terrain_events: EventWriter<TerrainDestroyed>,
     mut explosion_query: Query<(Entity, &Transform, &mut Explosion)>,
-    mut worm_query: Query<(&Transform, &mut Worm), Without<Explosion>>,
+    worm_query: Query<(Entity, &Transform, &Collider), (With<Worm>, Without<Explosion>)>,
+    prop_query: Query<(Entity, &Transform, &Collider), (With<crate::game::props::Prop>, Without<Explosion>)>,
+    mut mine_query: Query<(Entity, &Transform, &Collider, &mut crate::game::placed::ProximityTrigger), Without<Explosion>>,
+    mut gravestone_query: Query<(&Transform, &mut RigidBody), (With<crate::game::worm::Gravestone>, Without<Explosion>)>,
 ) {
     for (entity, transform, mut explosion) in explosion_query.iter_mut() {
-        explosion.lifetime.tick(time.delta());
-        
-        if explosion.lifetime.just_finished() {
-            // Damage worms in explosion radius
-            for (worm_transform, mut worm) in worm_query.iter_mut() {
-                let distance = transform.translation.distance(worm_transform.translation);
-                if distance <= explosion.radius {
-                    let damage_ratio = 1.0 - (distance / explosion.radius);
-                    let damage = explosion.damage * damage_ratio;
-                    worm.health -= damage;
-                    worm.health = worm.health.max(0.0);
+        // Damage is dealt the moment the blast goes off, the visual lingers afterwards
+        if !explosion.damage_applied {
+            explosion.damage_applied = true;
+            let center = transform.translation.truncate();
+            
+            // Damage worms, barrels and crates in explosion radius
+            for (target, target_transform, collider) in worm_query.iter().chain(prop_query.iter()) {
+                let damage = damage_model.explosion_damage(
+                    &terrain,
+                    center,
+                    explosion.radius,
+                    explosion.damage,
+                    target_transform.translation.truncate(),
+                    collider.radius,
+                );
+                if damage > 0.0 {
+                    damage_events.write(ApplyDamage {
+                        target,
+                        amount: damage,
+                        source_team: explosion.source_team,
+                        cause: explosion.cause.clone(),
+                    });
                 }
             }
             
-            // End turn after explosion
-            game_state.end_turn();
+            // Blasts set off any mines they reach
+            for (mine_entity, mine_transform, collider, mut trigger) in mine_query.iter_mut() {
+                let distance = center.distance(mine_transform.translation.truncate()) - collider.radius;
+                if distance <= explosion.radius && !trigger.triggered {
+                    trigger.triggered = true;
+                    commands.entity(mine_entity).insert(crate::game::placed::Fuse::new(0.3));
+                }
+            }
+            
+            // Knock gravestones away from the blast
+            for (grave_transform, mut body) in gravestone_query.iter_mut() {
+                let offset = grave_transform.translation.truncate() - center;
+                let distance = offset.length();
+                if distance <= explosion.radius {
+                    let strength = GRAVESTONE_KNOCKBACK * (1.0 - distance / explosion.radius);
+                    let direction = (offset.normalize_or_zero() + Vec2::Y).normalize_or_zero();
+                    body.velocity += direction * strength;
+                }
+            }
             
+            // Carve the crater after damage so terrain in the way still shields targets
+            let world_x = center.x + (terrain.width as f32 / 2.0);
+            let world_y = center.y + (terrain.height as f32 / 2.0);
+            terrain.destroy_circle(world_x, world_y, explosion.radius);
+            terrain_events.write(TerrainDestroyed {
+                center,
+                radius: explosion.radius,
+            });
+        }
+        
+        explosion.lifetime.tick(time.delta());
+        
+        if explosion.lifetime.just_finished() {
             // Remove explosion effect
             commands.entity(entity).despawn();
         }
     }
 }
 
+fn update_wind_system(
+    time: Res<Time>,
+    mut wind: ResMut<WindSystem>,
+    mut rng: ResMut<MatchRng>,
+    scheme: Res<crate::game::scheme::GameScheme>,
+) {
+    wind.change_timer.tick(time.delta());
+    if wind.change_timer.just_finished() {
+        wind.generate_new_wind(&mut rng, scheme.max_wind);
+    }
+}
+
 fn cleanup_expired_projectiles(
     mut commands: Commands,
     query: Query<(Entity, &Transform), With<Projectile>>,
@@ -353,27 +721,20 @@ fn cleanup_expired_projectiles(
     }
 }
 
-fn change_wind_on_turn_end(
-    mut wind_system: ResMut<WindSystem>,
+fn hand_weapons_to_team(
+    mut inventory: ResMut<WeaponInventory>,
     game_state: Res<crate::game::game_state::GameState>,
 ) {
-    // Change wind when turn transitions happen
-    if game_state.game_phase == crate::game::game_state::GamePhase::TurnTransition {
-        // Only change wind occasionally (30% chance)
-        if fastrand::f32() < 0.3 {
-            wind_system.generate_new_wind();
-        }
-    }
-}
-    }
+    inventory.hand_to(game_state.current_player, game_state.teams.len());
 }
 
-fn update_wind(
-    time: Res<Time>,
-    mut wind: ResMut<WindSystem>,
+fn change_wind_on_turn_end(
+    mut wind_system: ResMut<WindSystem>,
+    mut rng: ResMut<MatchRng>,
+    scheme: Res<crate::game::scheme::GameScheme>,
 ) {
-    wind.change_timer.tick(time.delta());
-    if wind.change_timer.just_finished() {
-        wind.generate_new_wind();
+    // Only change wind occasionally (30% chance)
+    if rng.f32() < 0.3 {
+        wind_system.generate_new_wind(&mut rng, scheme.max_wind);
     }
-}
\ No newline at end of file
+}
@@ -1,40 +1,54 @@
 use bevy::prelude::*;
-use crate::game::physics::{RigidBody, Collider};
-use crate::game::terrain::TerrainMap;
+use crate::game::physics::{RigidBody, Collider, TerrainBody};
+use crate::game::events::{ApplyDamage, DamageCause, WormDied, WormCommand, IssueWormCommands};
+use crate::game::scheme::{GameScheme, MatchSettings, TeamController};
+use crate::game::game_state::GameState;
+use crate::game::profiles::GravestoneStyle;
+use crate::game::replay::simulation_running;
+use crate::game::lifecycle::MatchRng;
 
 pub struct WormPlugin;
 
 impl Plugin for WormPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(Startup, spawn_worms)
             .add_systems(Update, (
-                worm_movement,
-                worm_terrain_collision,
+                worm_movement.after(IssueWormCommands),
                 update_worm_health_display,
                 handle_worm_death,
                 worm_fall_damage,
-            ));
+            ).run_if(simulation_running));
     }
 }
 
 #[derive(Component)]
 pub struct Worm {
+    pub name: String,
     pub health: f32,
     pub max_health: f32,
     pub team: u32,
     pub move_speed: f32,
     pub jump_force: f32,
+    pub facing: f32, // -1.0 = left, 1.0 = right
+    /// Damage taken this turn, counted off `health` when the turn resolves
+    pub pending_damage: f32,
+    pub last_attacker: Option<u32>,
+    pub last_damage_cause: Option<DamageCause>,
 }
 
 impl Default for Worm {
     fn default() -> Self {
         Self {
+            name: String::new(),
             health: 100.0,
             max_health: 100.0,
             team: 0,
             move_speed: 150.0,
             jump_force: 400.0,
+            facing: 1.0,
+            pending_damage: 0.0,
+            last_attacker: None,
+            last_damage_cause: None,
         }
     }
 }
@@ -42,128 +56,180 @@ impl Default for Worm {
 #[derive(Component)]
 pub struct PlayerControlled;
 
+/// Worms of a team played from elsewhere. Nothing local moves them.
+#[derive(Component)]
+pub struct RemoteControlled;
+
+/// The worm whose team is playing and who gets to move this turn.
+#[derive(Component)]
+pub struct ActiveWorm;
+
 #[derive(Component)]
 pub struct HealthBar;
 
+/// The worm's name, floating above its health bar.
+#[derive(Component)]
+pub struct WormLabel;
+
+/// Left behind where a worm died. Keeps falling with the terrain and gets pushed
+/// around by blasts, but is no longer a worm as far as turns and targeting go.
 #[derive(Component)]
-pub struct DeadWorm;
+pub struct Gravestone {
+    pub team: u32,
+}
+
+// Blast left behind by a worm dying, as in classic Worms
+const DEATH_EXPLOSION_RADIUS: f32 = 40.0;
+const DEATH_EXPLOSION_DAMAGE: f32 = 25.0;
 
-fn spawn_worms(
+pub fn spawn_worms(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<MatchRng>,
     terrain: Res<crate::game::terrain::TerrainMap>,
+    scheme: Res<GameScheme>,
+    settings: Res<MatchSettings>,
+    game_state: Res<GameState>,
 ) {
     // Calculate spawn positions based on terrain size
     let terrain_width = terrain.width as f32;
     let terrain_height = terrain.height as f32;
-    
-    // Spawn player worm on left side
-    let player_x = -terrain_width * 0.3;
-    let player_y = terrain_height * 0.3;
-    
-    commands.spawn((
+
+    // Spread the worms evenly across the map, with the teams mixed up
+    let slot_count = settings.teams.len() * settings.worms_per_team as usize;
+    let mut slots: Vec<usize> = (0..slot_count).collect();
+    rng.shuffle(&mut slots);
+    let mut slots = slots.into_iter();
+
+    for (team_id, team_setup) in settings.teams.iter().enumerate() {
+        let color = game_state.teams.get(team_id)
+            .map(|team| team.color)
+            .unwrap_or(Color::WHITE);
+
+        for worm_index in 0..settings.worms_per_team as usize {
+            let slot = slots.next().unwrap_or(0);
+            let x = -terrain_width * 0.4 + terrain_width * 0.8 * (slot as f32 + 0.5) / slot_count as f32;
+            let y = terrain_height * 0.3;
+
+            spawn_worm(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                Worm {
+                    name: team_setup.worm_name(worm_index),
+                    team: team_id as u32,
+                    health: scheme.worm_health,
+                    max_health: scheme.worm_health,
+                    ..default()
+                },
+                Vec3::new(x, y, 1.0),
+                color,
+                team_setup.controller,
+            );
+        }
+    }
+}
+
+/// Spawns one worm with its name label, controlled the way its team is.
+pub fn spawn_worm(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    worm: Worm,
+    position: Vec3,
+    color: Color,
+    controller: TeamController,
+) -> Entity {
+    let name = worm.name.clone();
+    let mut entity = commands.spawn((
         Mesh2d(meshes.add(bevy::math::primitives::Circle::new(16.0))),
-        MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(0.2, 0.8, 0.2)))),
-        Transform::from_translation(Vec3::new(player_x, player_y, 1.0)),
-        Worm {
-            team: 0,
-            ..default()
-        },
+        MeshMaterial2d(materials.add(ColorMaterial::from(color))),
+        Transform::from_translation(position),
+        worm,
         RigidBody::default(),
         Collider::default(),
-        PlayerControlled,
+        TerrainBody,
     ));
-    
-    // Spawn enemy worm on right side
-    let enemy_x = terrain_width * 0.3;
-    let enemy_y = terrain_height * 0.3;
-    
+    entity.with_children(|parent| {
+        parent.spawn((
+            Text2d::new(name),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(color),
+            Transform::from_translation(Vec3::new(0.0, 38.0, 0.3)),
+            WormLabel,
+        ));
+    });
+
+    match controller {
+        TeamController::Human => entity.insert(PlayerControlled),
+        TeamController::AI => entity.insert(crate::game::ai::AIControlled),
+        TeamController::Remote => entity.insert(RemoteControlled),
+    };
+    entity.id()
+}
+
+/// Spawns a gravestone, moving at `velocity` if it was knocked or thrown.
+pub fn spawn_gravestone(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    style: GravestoneStyle,
+    team: u32,
+    position: Vec3,
+    velocity: Vec2,
+) {
     commands.spawn((
-        Mesh2d(meshes.add(bevy::math::primitives::Circle::new(16.0))),
-        MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(0.8, 0.2, 0.2)))),
-        Transform::from_translation(Vec3::new(enemy_x, enemy_y, 1.0)),
-        Worm {
-            team: 1,
-            ..default()
+        Mesh2d(meshes.add(style.mesh())),
+        MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(0.55, 0.55, 0.6)))),
+        Transform::from_translation(position),
+        Gravestone { team },
+        RigidBody {
+            velocity,
+            bounce: 0.2,
+            friction: 0.6,
+            gravity_scale: 1.0,
         },
-        RigidBody::default(),
-        Collider::default(),
-        crate::game::ai::AIControlled, // Make enemy worm AI-controlled
+        Collider {
+            radius: 10.0,
+            is_grounded: false,
+        },
+        TerrainBody,
     ));
 }
 
 fn worm_movement(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    game_state: Res<crate::game::game_state::GameState>,
-    mut query: Query<(&mut RigidBody, &Worm, &Collider), (With<PlayerControlled>, Without<DeadWorm>)>,
+    mut worm_commands: EventReader<WormCommand>,
+    turn: crate::game::game_state::TurnFlow,
+    mut query: Query<(&mut RigidBody, &mut Worm, &Collider), With<ActiveWorm>>,
 ) {
-    // Only allow movement during player's turn
-    if !game_state.can_player_act() {
+    // Only allow movement during player's turn or while retreating
+    if !turn.can_player_move() {
+        worm_commands.clear();
         return;
     }
     
-    for (mut body, worm, collider) in query.iter_mut() {
-        if worm.health <= 0.0 || worm.team != game_state.current_player {
-            continue;
-        }
-        
-        let mut movement = 0.0;
-        
-        if keyboard_input.pressed(KeyCode::ArrowLeft) {
-            movement -= 1.0;
-        }
-        if keyboard_input.pressed(KeyCode::ArrowRight) {
-            movement += 1.0;
-        }
-        
-        // Apply horizontal movement
-        body.velocity.x = movement * worm.move_speed;
-        
-        // Jump - use different key to avoid conflict with aiming
-        if keyboard_input.just_pressed(KeyCode::KeyW) && collider.is_grounded {
-            body.velocity.y = worm.jump_force;
-        }
-    }
-}
-
-fn worm_terrain_collision(
-    terrain: Res<TerrainMap>,
-    mut query: Query<(&mut Transform, &mut RigidBody, &mut Collider), With<Worm>>,
-) {
-    for (mut transform, mut body, mut collider) in query.iter_mut() {
-        let world_x = transform.translation.x + (terrain.width as f32 / 2.0);
-        let world_y = transform.translation.y + (terrain.height as f32 / 2.0);
-        
-        // Check collision with terrain
-        if terrain.check_collision(world_x, world_y, collider.radius) {
-            // Simple collision response - push out of terrain
-            let mut found_safe_position = false;
-            
-            // Try moving up to find safe position
-            for offset in 1..=32 {
-                let test_y = world_y + offset as f32;
-                if !terrain.check_collision(world_x, test_y, collider.radius) {
-                    transform.translation.y = test_y - (terrain.height as f32 / 2.0);
-                    body.velocity.y = body.velocity.y.max(0.0); // Stop downward movement
-                    collider.is_grounded = true;
-                    found_safe_position = true;
-                    break;
-                }
+    for command in worm_commands.read() {
+        for (mut body, mut worm, collider) in query.iter_mut() {
+            if worm.health <= 0.0 {
+                continue;
             }
             
-            if !found_safe_position {
-                // If we can't find a safe position above, try moving horizontally
-                for x_offset in [-1, 1] {
-                    for offset in 1..=32 {
-                        let test_x = world_x + (x_offset * offset) as f32;
-                        if !terrain.check_collision(test_x, world_y, collider.radius) {
-                            transform.translation.x = test_x - (terrain.width as f32 / 2.0);
-                            body.velocity.x = 0.0;
-                            break;
-                        }
+            match *command {
+                WormCommand::Move(direction) => {
+                    let direction = direction.clamp(-1.0, 1.0);
+                    body.velocity.x = direction * worm.move_speed;
+                    if direction != 0.0 {
+                        worm.facing = direction.signum();
                     }
                 }
+                WormCommand::Jump if collider.is_grounded => {
+                    body.velocity.y = worm.jump_force;
+                }
+                _ => {}
             }
         }
     }
@@ -173,7 +239,7 @@ fn update_worm_health_display(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    worm_query: Query<(Entity, &Transform, &Worm), Without<DeadWorm>>,
+    worm_query: Query<(Entity, &Transform, &Worm)>,
     health_bar_query: Query<Entity, With<HealthBar>>,
 ) {
     // Remove existing health bars
@@ -226,33 +292,66 @@ fn update_worm_health_display(
 
 fn handle_worm_death(
     mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    mut worm_query: Query<(Entity, &mut MeshMaterial2d<ColorMaterial>, &Worm), (Without<DeadWorm>, Changed<Worm>)>,
+    mut rng: ResMut<MatchRng>,
+    mut turn: crate::game::game_state::TurnFlow,
+    game_state: Res<GameState>,
+    mut death_events: EventReader<WormDied>,
+    worm_query: Query<(&Transform, &RigidBody), With<Worm>>,
 ) {
-    for (entity, mut material, worm) in worm_query.iter_mut() {
-        if worm.health <= 0.0 {
-            // Mark worm as dead
-            commands.entity(entity).insert(DeadWorm);
-            
-            // Change worm color to indicate death
-            material.0 = materials.add(ColorMaterial::from(Color::srgba(0.5, 0.5, 0.5, 0.7)));
-            
-            // Remove physics components to make it static
-            commands.entity(entity).remove::<RigidBody>();
-            commands.entity(entity).remove::<PlayerControlled>();
-        }
+    for event in death_events.read() {
+        let Ok((transform, body)) = worm_query.get(event.worm) else {
+            continue;
+        };
+        
+        // Swap the worm for a gravestone that keeps its momentum
+        commands.entity(event.worm).despawn();
+        
+        let style = game_state.teams.get(event.team as usize)
+            .map(|team| team.gravestone)
+            .unwrap_or(GravestoneStyle::Headstone);
+        
+        spawn_gravestone(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            style,
+            event.team,
+            transform.translation,
+            body.velocity,
+        );
+        
+        crate::game::weapons::detonate(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &mut rng,
+            &mut turn,
+            transform.translation,
+            DEATH_EXPLOSION_RADIUS,
+            DEATH_EXPLOSION_DAMAGE,
+            None,
+            DamageCause::Explosion,
+        );
     }
 }
 
 fn worm_fall_damage(
-    mut worm_query: Query<(&mut Worm, &RigidBody, &Collider), Without<DeadWorm>>,
+    scheme: Res<GameScheme>,
+    mut damage_events: EventWriter<ApplyDamage>,
+    worm_query: Query<(Entity, &RigidBody, &Collider), With<Worm>>,
 ) {
-    for (mut worm, body, collider) in worm_query.iter_mut() {
+    for (entity, body, collider) in worm_query.iter() {
         // Apply fall damage when hitting ground at high speed
-        if collider.is_grounded && body.velocity.y.abs() > 300.0 {
-            let fall_damage = (body.velocity.y.abs() - 300.0) * 0.1;
-            worm.health -= fall_damage;
-            worm.health = worm.health.max(0.0);
+        if collider.is_grounded && body.velocity.y.abs() > scheme.fall_damage_threshold {
+            let fall_damage = (body.velocity.y.abs() - scheme.fall_damage_threshold) * scheme.fall_damage_per_speed;
+            damage_events.write(ApplyDamage {
+                target: entity,
+                amount: fall_damage,
+                source_team: None,
+                cause: DamageCause::Fall,
+            });
         }
     }
-}
\ No newline at end of file
+}